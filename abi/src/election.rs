@@ -0,0 +1,277 @@
+use crate::pow::to_crypto_hash;
+use async_graphql::{Enum, SimpleObject};
+use linera_sdk::{
+    bcs,
+    linera_base_types::{AccountOwner, Amount, BlockHeight, CryptoHash, Timestamp},
+};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::BTreeMap;
+
+/// How a meme chain elects its block proposer. It's selected by meme creator.
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Enum)]
+pub enum LeaderElectionMethod {
+    // Chain consensus elects leader of each single leader round
+    SingleLeaderRoundRobin,
+    // Every owner could propose, meme application only accepts weighted round robin leader
+    #[default]
+    MultiLeaderRoundRobin,
+    // Every owner could propose, block must be mined by its proposer
+    ProofOfWork,
+    // Every owner could propose, meme application only accepts stake weighted leader
+    ProofOfStake,
+}
+
+impl LeaderElectionMethod {
+    // Multi leader rounds of meme chain ownership
+    pub fn multi_leader_rounds(&self) -> u32 {
+        match self {
+            LeaderElectionMethod::SingleLeaderRoundRobin => 0,
+            _ => u32::MAX,
+        }
+    }
+}
+
+/// Proposer election progress of a meme chain. Round of a height is counted from the timestamp of
+/// the previous block, so an offline leader is skipped once its round times out.
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderElection {
+    // Height of the last block which executed an operation
+    pub height: BlockHeight,
+    // Timestamp of the block before `height`
+    pub round_started_at: Timestamp,
+    // Timestamp of the block at `height`
+    pub block_time: Timestamp,
+    // Latest mined hash before the block at `height`, seed of its stake weighted election
+    pub previous_hash: CryptoHash,
+}
+
+impl LeaderElection {
+    pub fn new(height: BlockHeight, timestamp: Timestamp, previous_hash: CryptoHash) -> Self {
+        Self {
+            height,
+            round_started_at: timestamp,
+            block_time: timestamp,
+            previous_hash,
+        }
+    }
+
+    // Operations of the same block share the same round and seed, even if the block mines
+    pub fn advance(
+        &mut self,
+        height: BlockHeight,
+        timestamp: Timestamp,
+        previous_hash: CryptoHash,
+    ) {
+        if height == self.height {
+            return;
+        }
+        self.height = height;
+        self.round_started_at = self.block_time;
+        self.block_time = timestamp;
+        self.previous_hash = previous_hash;
+    }
+}
+
+/// Round at `elapsed_micros` after round 0 started. Round `r` lasts
+/// `base_timeout + r * timeout_increment`, the same as timeouts of chain rounds.
+pub fn election_round(elapsed_micros: u64, base_timeout: u64, timeout_increment: u64) -> u32 {
+    if base_timeout == 0 && timeout_increment == 0 {
+        return 0;
+    }
+
+    // End of round r: (r + 1) * base_timeout + r * (r + 1) / 2 * timeout_increment
+    let round_end = |round: u128| {
+        (round + 1) * u128::from(base_timeout)
+            + round * (round + 1) / 2 * u128::from(timeout_increment)
+    };
+
+    // Find the first round which doesn't end before elapsed time
+    let (mut low, mut high) = (0u128, u128::from(u32::MAX));
+    while low < high {
+        let mid = (low + high) / 2;
+        if round_end(mid) > u128::from(elapsed_micros) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    low as u32
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Weighted round robin. Height walks slots of owners in canonical order, each owner takes as
+/// many slots as its weight reduced by common divisor of all weights. Every following round
+/// moves to the next owner, so a timed out leader is not elected twice in a row.
+pub fn round_robin_leader(
+    owner_weights: &BTreeMap<AccountOwner, u64>,
+    height: BlockHeight,
+    round: u32,
+) -> Option<AccountOwner> {
+    let owners = owner_weights
+        .iter()
+        .filter(|(_, weight)| **weight > 0)
+        .collect::<Vec<_>>();
+    let divisor = owners
+        .iter()
+        .fold(0, |divisor, (_, weight)| gcd(divisor, **weight));
+    if divisor == 0 {
+        return None;
+    }
+    let total_weight = owners
+        .iter()
+        .map(|(_, weight)| u128::from(**weight / divisor))
+        .sum::<u128>();
+
+    let mut slot = u128::from(height.0) % total_weight;
+    let index = owners
+        .iter()
+        .position(|(_, weight)| {
+            let weight = u128::from(**weight / divisor);
+            if slot < weight {
+                return true;
+            }
+            slot -= weight;
+            false
+        })
+        .unwrap();
+
+    let index = (index + round as usize % owners.len()) % owners.len();
+    Some(*owners[index].0)
+}
+
+/// Seed of stake weighted election. It only depends on chain history, so everyone could verify
+/// the elected leader. Block timestamp is chosen by the previous proposer, so the seed uses the
+/// latest mined hash instead, which could only be biased by mining again.
+pub fn election_seed(height: BlockHeight, round: u32, previous_hash: CryptoHash) -> CryptoHash {
+    let bytes = bcs::to_bytes(&(height, round, previous_hash)).expect("Failed serialize seed");
+    to_crypto_hash(Keccak256::digest(&bytes).into())
+}
+
+/// Draw leader from stakes with probability proportional to stake.
+pub fn stake_weighted_leader(
+    stakes: &BTreeMap<AccountOwner, Amount>,
+    seed: CryptoHash,
+) -> Option<AccountOwner> {
+    let total_stake = stakes.values().fold(U256::zero(), |total, stake| {
+        total + U256::from(u128::from(*stake))
+    });
+    if total_stake.is_zero() {
+        return None;
+    }
+
+    let mut point = U256::from_big_endian(seed.as_bytes().as_slice()) % total_stake;
+    for (owner, stake) in stakes {
+        let stake = U256::from(u128::from(*stake));
+        if point < stake {
+            return Some(*owner);
+        }
+        point -= stake;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_sdk::linera_base_types::{AccountOwner, Amount, BlockHeight, CryptoHash};
+    use std::{collections::BTreeMap, str::FromStr};
+
+    use super::{election_round, election_seed, round_robin_leader, stake_weighted_leader};
+
+    fn owner(index: u8) -> AccountOwner {
+        AccountOwner::from_str(&format!("0x{}", format!("{:02x}", index).repeat(32))).unwrap()
+    }
+
+    #[test]
+    fn test_election_round() {
+        // Rounds last 10, 11, 12 ... seconds
+        assert_eq!(election_round(0, 10_000_000, 1_000_000), 0);
+        assert_eq!(election_round(9_999_999, 10_000_000, 1_000_000), 0);
+        assert_eq!(election_round(10_000_000, 10_000_000, 1_000_000), 1);
+        assert_eq!(election_round(20_999_999, 10_000_000, 1_000_000), 1);
+        assert_eq!(election_round(21_000_000, 10_000_000, 1_000_000), 2);
+        assert!(election_round(u64::MAX, 10_000_000, 1_000_000) > 2);
+        assert_eq!(election_round(u64::MAX, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_round_robin_leader() {
+        let owner_weights = BTreeMap::from([(owner(1), 200), (owner(2), 100), (owner(3), 100)]);
+
+        let leaders = (0..8)
+            .map(|height| round_robin_leader(&owner_weights, BlockHeight::from(height), 0))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            leaders,
+            [1, 1, 2, 3, 1, 1, 2, 3]
+                .iter()
+                .map(|index| Some(owner(*index)))
+                .collect::<Vec<_>>()
+        );
+
+        // Timed out leader is skipped
+        assert_eq!(
+            round_robin_leader(&owner_weights, BlockHeight::from(0), 1),
+            Some(owner(2))
+        );
+        assert_eq!(
+            round_robin_leader(&owner_weights, BlockHeight::from(0), 3),
+            Some(owner(1))
+        );
+
+        assert_eq!(
+            round_robin_leader(&BTreeMap::new(), BlockHeight::from(0), 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_stake_weighted_leader() {
+        let stakes = BTreeMap::from([
+            (owner(1), Amount::from_tokens(300)),
+            (owner(2), Amount::from_tokens(100)),
+            (owner(3), Amount::ZERO),
+        ]);
+
+        let mut elected = BTreeMap::new();
+        for height in 0..400 {
+            let seed = election_seed(BlockHeight::from(height), 0, CryptoHash::from([0u64; 4]));
+            let leader = stake_weighted_leader(&stakes, seed).unwrap();
+            *elected.entry(leader).or_insert(0) += 1;
+
+            // Election is deterministic
+            assert_eq!(stake_weighted_leader(&stakes, seed), Some(leader));
+        }
+
+        // Owner without stake is never elected, others are elected by stake weight
+        assert_eq!(elected.get(&owner(3)), None);
+        assert!(elected[&owner(1)] > elected[&owner(2)]);
+
+        // Seed changes with round
+        assert_ne!(
+            election_seed(BlockHeight::from(0), 0, CryptoHash::from([0u64; 4])),
+            election_seed(BlockHeight::from(0), 1, CryptoHash::from([0u64; 4]))
+        );
+        // Seed changes with mined hash
+        assert_ne!(
+            election_seed(BlockHeight::from(0), 0, CryptoHash::from([0u64; 4])),
+            election_seed(BlockHeight::from(0), 0, CryptoHash::from([1u64; 4]))
+        );
+        assert_eq!(
+            stake_weighted_leader(
+                &BTreeMap::new(),
+                election_seed(BlockHeight::from(0), 0, CryptoHash::from([0u64; 4]))
+            ),
+            None
+        );
+    }
+}
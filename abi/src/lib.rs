@@ -2,8 +2,10 @@ pub mod ams;
 pub mod approval;
 pub mod blob_gateway;
 pub mod deposit;
+pub mod election;
 pub mod meme;
 pub mod policy;
+pub mod pow;
 pub mod proxy;
 pub mod store_type;
 pub mod swap;
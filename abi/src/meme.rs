@@ -1,11 +1,14 @@
-use crate::store_type::StoreType;
-use async_graphql::{scalar, InputObject, Request, Response, SimpleObject};
+use crate::{election::LeaderElectionMethod, store_type::StoreType};
+use async_graphql::{scalar, Enum, InputObject, Request, Response, SimpleObject};
 use linera_sdk::{
+    bcs,
     graphql::GraphQLMutationRoot,
     linera_base_types::{
-        Account, Amount, ApplicationId, ChainId, ContractAbi, CryptoHash, ServiceAbi,
+        Account, AccountOwner, AccountPublicKey, AccountSignature, Amount, ApplicationId,
+        BcsSignable, BlockHeight, ChainId, ContractAbi, CryptoHash, ServiceAbi, Timestamp,
     },
 };
+use primitive_types::U256;
 use serde::{Deserialize, Serialize};
 
 #[derive(
@@ -33,6 +36,745 @@ pub struct Liquidity {
 
 scalar!(Liquidity);
 
+pub const MAX_DESCRIPTION_LENGTH: usize = 4096;
+pub const MAX_LINK_LENGTH: usize = 256;
+
+impl Metadata {
+    // Links could be url or handle, frontends show them as is
+    pub fn validate(&self) -> bool {
+        let link_valid = |link: &Option<String>| {
+            link.as_deref().is_none_or(|link| {
+                !link.is_empty()
+                    && link.len() <= MAX_LINK_LENGTH
+                    && !link.contains(char::is_whitespace)
+            })
+        };
+        self.logo.is_some()
+            && self.description.len() <= MAX_DESCRIPTION_LENGTH
+            && [
+                &self.twitter,
+                &self.telegram,
+                &self.discord,
+                &self.website,
+                &self.github,
+                &self.live_stream,
+            ]
+            .into_iter()
+            .all(link_valid)
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Enum)]
+pub enum HashAlgorithm {
+    Sha256d,
+    #[default]
+    Keccak256,
+    Blake3,
+    // Memory-hard
+    Scrypt,
+}
+
+#[derive(
+    Default, Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct MiningConfig {
+    // Hash of mine nonce must not be greater than U256::MAX / difficulty
+    pub initial_difficulty: u64,
+    // Expected interval between two mined blocks in milliseconds
+    pub target_block_time_ms: u64,
+    // Difficulty is adjusted after each retarget_interval mined blocks
+    pub retarget_interval: u64,
+    // Overwritten by meme parameters when meme is created
+    pub pow_algorithm: HashAlgorithm,
+    // None means mined block is rewarded to the miner solo
+    pub mining_pool: Option<MiningPool>,
+}
+
+#[derive(
+    Default, Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct MiningPool {
+    // Share difficulty is block difficulty divided by this divisor
+    pub share_difficulty_divisor: u64,
+    // Proposer reward of each mined block is split by the last pplns_window shares
+    pub pplns_window: u64,
+}
+
+impl MiningPool {
+    pub fn validate(&self) {
+        assert!(
+            self.share_difficulty_divisor > 0,
+            "Invalid share difficulty divisor"
+        );
+        assert!(self.pplns_window > 0, "Invalid PPLNS window");
+    }
+
+    pub fn share_difficulty(&self, difficulty: u64) -> u64 {
+        (difficulty / self.share_difficulty_divisor.max(1)).max(1)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct MiningShare {
+    pub miner: Account,
+    pub hash: CryptoHash,
+    pub height: BlockHeight,
+    // Share difficulty when it's submitted, shares are weighted by it in PPLNS settlement
+    pub difficulty: u64,
+}
+
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Enum)]
+pub enum RewardDecay {
+    // Block reward is halved at each decay
+    #[default]
+    Halving,
+    // Block reward is reduced by decay_rate_bps at each decay
+    Exponential,
+}
+
+#[derive(
+    Default, Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct StakingConfig {
+    // Unstaked balance could only be claimed after unbonding period
+    pub unbonding_period_ms: u64,
+    pub min_stake: Amount,
+}
+
+impl StakingConfig {
+    pub fn validate(&self) {
+        assert!(self.unbonding_period_ms > 0, "Invalid unbonding period");
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Enum)]
+pub enum TaxDestination {
+    // Tax is removed from total supply
+    #[default]
+    Burn,
+    Treasury,
+    // Tax is distributed to holders pro rata to their balances
+    Reflection,
+}
+
+pub const MAX_TRANSFER_TAX_BPS: u16 = 2500;
+
+#[derive(
+    Default, Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferTax {
+    // Transfer from meme native pool to holder
+    pub buy_bps: u16,
+    // Transfer from holder to meme native pool
+    pub sell_bps: u16,
+    // Other transfers between holders
+    pub transfer_bps: u16,
+    pub destination: TaxDestination,
+    pub treasury: Option<Account>,
+}
+
+impl TransferTax {
+    const BPS: u16 = 10000;
+    // Reflection of each balance unit is scaled to keep precision of small tax over large supply
+    const REFLECTION_SCALE: u128 = 1_000_000_000_000_000_000;
+
+    pub fn validate(&self) {
+        assert!(
+            self.buy_bps <= MAX_TRANSFER_TAX_BPS
+                && self.sell_bps <= MAX_TRANSFER_TAX_BPS
+                && self.transfer_bps <= MAX_TRANSFER_TAX_BPS,
+            "Invalid transfer tax"
+        );
+        assert!(
+            (self.destination == TaxDestination::Treasury) == self.treasury.is_some(),
+            "Invalid treasury"
+        );
+    }
+
+    pub fn tax(amount: Amount, bps: u16) -> Amount {
+        Amount::from_attos(
+            (U256::from(u128::from(amount)) * U256::from(bps) / U256::from(Self::BPS)).as_u128(),
+        )
+    }
+
+    // Scaled reflection of each balance unit when amount is reflected to supply
+    pub fn reflection_per_token(amount: Amount, supply: Amount) -> u128 {
+        (U256::from(u128::from(amount)) * U256::from(Self::REFLECTION_SCALE)
+            / U256::from(u128::from(supply)))
+        .min(U256::from(u128::MAX))
+        .as_u128()
+    }
+
+    // Rounded down, so reflections of all holders never exceed reflected amount
+    pub fn reflection_of(balance: Amount, per_token: u128) -> Amount {
+        Amount::from_attos(
+            (U256::from(u128::from(balance)) * U256::from(per_token)
+                / U256::from(Self::REFLECTION_SCALE))
+            .min(U256::from(u128::MAX))
+            .as_u128(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub id: u64,
+    pub created_at: Timestamp,
+}
+
+/// Value before the first change after snapshot, it's the value at all snapshots from the
+/// previous checkpoint up to snapshot_id.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq)]
+pub struct Checkpoint {
+    pub snapshot_id: u64,
+    pub value: Amount,
+}
+
+impl Checkpoint {
+    pub fn value_at(checkpoints: &[Checkpoint], snapshot_id: u64, current: Amount) -> Amount {
+        let index = checkpoints.partition_point(|checkpoint| checkpoint.snapshot_id < snapshot_id);
+        checkpoints
+            .get(index)
+            .map_or(current, |checkpoint| checkpoint.value)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Unbonding {
+    pub amount: Amount,
+    pub release_at: Timestamp,
+}
+
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Enum)]
+pub enum VestingRelease {
+    // Vested amount grows with every millisecond after cliff
+    #[default]
+    Linear,
+    // Vested amount grows at the end of each step
+    Step,
+}
+
+#[derive(
+    Default, Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct VestingSchedule {
+    pub amount: Amount,
+    // Nothing could be claimed before cliff
+    pub cliff_ms: u64,
+    // Whole amount is vested after duration, counted from meme creation including cliff
+    pub duration_ms: u64,
+    pub release: VestingRelease,
+    // Only used by step release
+    pub step_ms: u64,
+}
+
+impl VestingSchedule {
+    pub fn validate(&self) {
+        assert!(self.amount > Amount::ZERO, "Invalid vesting amount");
+        assert!(self.duration_ms > 0, "Invalid vesting duration");
+        assert!(self.cliff_ms <= self.duration_ms, "Invalid vesting cliff");
+        assert!(
+            self.release != VestingRelease::Step || self.step_ms > 0,
+            "Invalid vesting step"
+        );
+    }
+
+    // Total vested amount at elapsed_ms after vesting started, including claimed amount
+    pub fn vested(&self, elapsed_ms: u64) -> Amount {
+        if elapsed_ms < self.cliff_ms {
+            return Amount::ZERO;
+        }
+        if elapsed_ms >= self.duration_ms {
+            return self.amount;
+        }
+
+        let elapsed_ms = match self.release {
+            VestingRelease::Linear => elapsed_ms,
+            VestingRelease::Step => elapsed_ms / self.step_ms * self.step_ms,
+        };
+        Amount::from_attos(
+            (U256::from(u128::from(self.amount)) * U256::from(elapsed_ms)
+                / U256::from(self.duration_ms))
+            .as_u128(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamAllocation {
+    pub beneficiary: Account,
+    pub schedule: VestingSchedule,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Vesting {
+    pub schedule: VestingSchedule,
+    pub started_at: Timestamp,
+    pub claimed: Amount,
+}
+
+impl Vesting {
+    // Vested but not claimed amount at timestamp
+    pub fn claimable(&self, timestamp: Timestamp) -> Amount {
+        let elapsed_ms = timestamp.delta_since(self.started_at).as_micros() / 1000;
+        self.schedule
+            .vested(elapsed_ms)
+            .saturating_sub(self.claimed)
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Enum)]
+pub enum AirdropSplit {
+    #[default]
+    Equal,
+    // Split by weights configured by creator
+    Weighted,
+    // Split equally, then each share is released by vesting schedule
+    Vested,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropWeight {
+    pub miner: Account,
+    pub weight: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisMinerAirdrop {
+    // Total airdrop amount, taken from application balance
+    pub amount: Amount,
+    pub split: AirdropSplit,
+    // Used by weighted split, genesis miners without weight get nothing
+    pub weights: Vec<AirdropWeight>,
+    // Used by vested split, amount of schedule is replaced by share of each miner
+    pub vesting: Option<VestingSchedule>,
+}
+
+impl GenesisMinerAirdrop {
+    pub fn validate(&self) {
+        assert!(self.amount > Amount::ZERO, "Invalid airdrop amount");
+        match self.split {
+            AirdropSplit::Equal => {}
+            AirdropSplit::Weighted => assert!(
+                self.weights.iter().any(|weight| weight.weight > 0),
+                "Invalid airdrop weights"
+            ),
+            AirdropSplit::Vested => assert!(self.vesting.is_some(), "Invalid airdrop vesting"),
+        }
+    }
+
+    // Rounding dust is kept by application
+    pub fn shares(&self, miners: &[Account]) -> Vec<(Account, Amount)> {
+        let weights = miners
+            .iter()
+            .map(|miner| match self.split {
+                AirdropSplit::Weighted => self
+                    .weights
+                    .iter()
+                    .filter(|weight| weight.miner == *miner)
+                    .map(|weight| weight.weight)
+                    .sum::<u64>(),
+                _ => 1,
+            })
+            .collect::<Vec<_>>();
+        let total_weight = weights
+            .iter()
+            .map(|weight| u128::from(*weight))
+            .sum::<u128>();
+        if total_weight == 0 {
+            return Vec::new();
+        }
+
+        miners
+            .iter()
+            .zip(weights)
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(miner, weight)| {
+                let share = U256::from(u128::from(self.amount)) * U256::from(weight)
+                    / U256::from(total_weight);
+                (*miner, Amount::from_attos(share.as_u128()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct WhitelistAllocation {
+    pub account: Account,
+    // Max meme tokens the account could buy in presale
+    pub cap: Amount,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct PresaleConfig {
+    pub whitelist: Vec<WhitelistAllocation>,
+    // Native tokens paid for one meme token
+    pub price: Amount,
+    // Nothing could be bought after presale ends, then pool could be opened
+    pub ends_at: Timestamp,
+    // Purchased tokens are locked until release time
+    pub release_at: Timestamp,
+}
+
+impl PresaleConfig {
+    pub fn validate(&self) {
+        assert!(!self.whitelist.is_empty(), "Invalid whitelist");
+        assert!(
+            self.whitelist
+                .iter()
+                .all(|allocation| allocation.cap > Amount::ZERO),
+            "Invalid allocation cap"
+        );
+        assert!(self.price > Amount::ZERO, "Invalid presale price");
+        assert!(self.release_at >= self.ends_at, "Invalid release time");
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Presale {
+    pub price: Amount,
+    pub ends_at: Timestamp,
+    pub release_at: Timestamp,
+    // Sum of allocation caps, reserved from application balance
+    pub reserved: Amount,
+    pub sold: Amount,
+    // Native tokens raised, added to initial liquidity when pool is opened
+    pub raised: Amount,
+    pub closed: bool,
+}
+
+impl Presale {
+    // Meme tokens bought with native amount at fixed price
+    pub fn tokens_for(&self, native_amount: Amount) -> Amount {
+        Amount::from_attos(
+            (U256::from(u128::from(native_amount)) * U256::from(u128::from(Amount::ONE))
+                / U256::from(u128::from(self.price)))
+            .try_into()
+            .unwrap_or(u128::MAX),
+        )
+    }
+}
+
+#[derive(
+    Default, Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct EmissionPolicy {
+    pub initial_block_reward: Amount,
+    pub decay: RewardDecay,
+    // Block reward is decayed after each decay_interval mined blocks
+    pub decay_interval: u64,
+    pub decay_rate_bps: u16,
+    // Initial supply and mined supply together will never exceed hard cap
+    pub hard_cap: Amount,
+}
+
+impl EmissionPolicy {
+    const BPS: u16 = 10000;
+    const SCALE: u128 = 1_000_000_000_000_000_000;
+
+    pub fn validate(&self, initial_supply: Amount) {
+        assert!(
+            self.initial_block_reward > Amount::ZERO,
+            "Invalid block reward"
+        );
+        assert!(self.decay_interval > 0, "Invalid decay interval");
+        assert!(self.decay_rate_bps <= Self::BPS, "Invalid decay rate");
+        assert!(self.hard_cap >= initial_supply, "Invalid hard cap");
+    }
+
+    // Reward of the block mined after mined_blocks blocks, without hard cap
+    pub fn block_reward(&self, mined_blocks: u64) -> Amount {
+        let decays = mined_blocks / self.decay_interval;
+        let reward = u128::from(self.initial_block_reward);
+
+        match self.decay {
+            RewardDecay::Halving => Amount::from_attos(
+                reward
+                    .checked_shr(decays.try_into().unwrap_or(u32::MAX))
+                    .unwrap_or(0),
+            ),
+            RewardDecay::Exponential => {
+                let factor = Self::pow(
+                    u128::from(Self::BPS - self.decay_rate_bps) * Self::SCALE
+                        / u128::from(Self::BPS),
+                    decays,
+                );
+                Amount::from_attos(
+                    (U256::from(reward) * U256::from(factor) / U256::from(Self::SCALE)).as_u128(),
+                )
+            }
+        }
+    }
+
+    // Scaled factor ^ exponent
+    fn pow(mut factor: u128, mut exponent: u64) -> u128 {
+        let mut result = Self::SCALE;
+        while exponent > 0 && result > 0 {
+            if exponent & 1 == 1 {
+                result = Self::mul(result, factor);
+            }
+            factor = Self::mul(factor, factor);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    fn mul(a: u128, b: u128) -> u128 {
+        (U256::from(a) * U256::from(b) / U256::from(Self::SCALE)).as_u128()
+    }
+}
+
+#[derive(
+    Default, Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RewardSplit {
+    // Signer of the block which contains the accepted mine operation
+    pub proposer_bps: u16,
+    // Creator in meme parameters
+    pub creator_bps: u16,
+    pub treasury_bps: u16,
+    pub treasury: Option<Account>,
+    // Topped up to meme native pool through swap application
+    pub liquidity_bps: u16,
+}
+
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct RewardShares {
+    pub proposer: Amount,
+    pub creator: Amount,
+    pub treasury: Amount,
+    pub liquidity: Amount,
+}
+
+impl RewardSplit {
+    const BPS: u16 = 10000;
+
+    pub fn validate(&self) {
+        let total_bps = u32::from(self.proposer_bps)
+            + u32::from(self.creator_bps)
+            + u32::from(self.treasury_bps)
+            + u32::from(self.liquidity_bps);
+        assert!(total_bps == u32::from(Self::BPS), "Invalid reward split");
+        assert!(
+            self.treasury_bps == 0 || self.treasury.is_some(),
+            "Invalid treasury"
+        );
+    }
+
+    // Rounding dust goes to proposer so the whole reward is always distributed
+    pub fn split(&self, reward: Amount) -> RewardShares {
+        let creator = Self::share(reward, self.creator_bps);
+        let treasury = Self::share(reward, self.treasury_bps);
+        let liquidity = Self::share(reward, self.liquidity_bps);
+        let proposer = reward
+            .saturating_sub(creator)
+            .saturating_sub(treasury)
+            .saturating_sub(liquidity);
+
+        RewardShares {
+            proposer,
+            creator,
+            treasury,
+            liquidity,
+        }
+    }
+
+    fn share(reward: Amount, bps: u16) -> Amount {
+        Amount::from_attos(
+            (U256::from(u128::from(reward)) * U256::from(bps) / U256::from(Self::BPS)).as_u128(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Allowance {
+    pub spender: Account,
+    pub amount: Amount,
+    // Expired allowance could not be spent, its escrow is returned to owner
+    pub expires_at: Option<Timestamp>,
+}
+
+impl Allowance {
+    pub fn expired(&self, timestamp: Timestamp) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| timestamp >= expires_at)
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Enum)]
+pub enum PendingOperationKind {
+    #[default]
+    Transfer,
+    TransferFrom,
+    BatchTransfer,
+    Approve,
+    DecreaseAllowance,
+    RevokeAllowance,
+    Permit,
+    TransferOwnership,
+    Snapshot,
+    UpdateMetadata,
+    Stake,
+    Unstake,
+    ClaimUnstaked,
+    ClaimVested,
+    PresaleBuy,
+    ClaimPresale,
+    Burn,
+    BurnFrom,
+}
+
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Enum)]
+pub enum PendingOperationStatus {
+    // Message is sent to creation chain but not replied yet
+    #[default]
+    Pending,
+    Succeeded,
+    // Failed on creation chain, or rejected then bounced back to sender chain
+    Failed,
+}
+
+// Only the latest resolved operations are kept in sender chain log
+pub const MAX_PENDING_OPERATIONS: usize = 100;
+
+/// Tracked message sent from user chain, it's resolved by reply from creation chain.
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingOperation {
+    pub id: u64,
+    pub kind: PendingOperationKind,
+    pub from: Account,
+    // Spender, new owner or recipient, batch transfer has multiple recipients
+    pub to: Option<Account>,
+    pub amount: Amount,
+    pub created_at: Timestamp,
+    pub status: PendingOperationStatus,
+    pub reason: Option<String>,
+}
+
+// Bound holders returned by one query
+pub const MAX_HOLDERS_PAGE: usize = 100;
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Holder {
+    pub account: Account,
+    // Settled balance, including reflections accrued since the last balance change
+    pub balance: Amount,
+}
+
+// Position of a holder in the holders index. Index is keyed by the stored balance, which excludes
+// reflections accrued since the last balance change, so it may differ from the settled balance
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq)]
+pub struct HolderCursor {
+    pub account: Account,
+    pub balance: Amount,
+}
+
+impl HolderCursor {
+    // Cursor is opaque to clients, it keeps the indexed balance so the next page starts from the
+    // holder's position in index without looking up its current balance
+    pub fn encode(&self) -> String {
+        bcs::to_bytes(&(self.balance, self.account))
+            .expect("Failed serialize cursor")
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        if cursor.len() % 2 != 0 || !cursor.is_ascii() {
+            return None;
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&cursor[index..index + 2], 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+        let (balance, account) = bcs::from_bytes(&bytes).ok()?;
+        Some(HolderCursor { account, balance })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct HolderPage {
+    pub holders: Vec<Holder>,
+    // Cursor of the last holder, passed as after to fetch the next page
+    pub end_cursor: Option<String>,
+    pub has_next_page: bool,
+}
+
+// Bound single message size of batch transfer
+pub const MAX_BATCH_TRANSFERS: usize = 500;
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchTransferItem {
+    pub to: Account,
+    pub amount: Amount,
+}
+
+/// Approval signed off-chain by owner, so anyone could submit it with permit operation.
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit {
+    // Meme application, permit could not be replayed on other tokens
+    pub token: ApplicationId,
+    pub owner: Account,
+    pub spender: Account,
+    pub amount: Amount,
+    // Must equal to the next permit nonce of owner
+    pub nonce: u64,
+    // Permit could not be applied at or after deadline
+    pub deadline: Timestamp,
+    // Expiry of the approved allowance, it's also signed so submitter couldn't extend it
+    pub expires_at: Option<Timestamp>,
+}
+
+impl<'de> BcsSignable<'de> for Permit {}
+
+impl Permit {
+    pub fn verify(&self, public_key: AccountPublicKey, signature: &AccountSignature) -> bool {
+        AccountOwner::from(public_key) == self.owner.owner
+            && signature.verify(self, public_key).is_ok()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct MiningInfo {
+    pub difficulty: u64,
+    // Height of the block which contains the last accepted mine operation
+    pub mined_height: Option<BlockHeight>,
+    // Hash of the last accepted nonce, or genesis hash of the chain
+    pub previous_hash: CryptoHash,
+    // Miner of the last accepted mine operation
+    pub miner: Option<Account>,
+    pub mined_blocks: u64,
+    // Timestamp of the block which starts current retarget window
+    pub window_started_at: Option<Timestamp>,
+    // Mined blocks after the block which starts current retarget window
+    pub window_blocks: u64,
+}
+
 #[derive(
     Default, Debug, Clone, Deserialize, Serialize, Eq, PartialEq, InputObject, SimpleObject,
 )]
@@ -46,6 +788,13 @@ pub struct Meme {
     pub metadata: Metadata,
     pub virtual_initial_liquidity: bool,
     pub initial_liquidity: Option<Liquidity>,
+    pub mining: MiningConfig,
+    // None means mined blocks won't be rewarded
+    pub emission: Option<EmissionPolicy>,
+    // None means holders could not stake for proposer election
+    pub staking: Option<StakingConfig>,
+    // None means transfers are not taxed
+    pub transfer_tax: Option<TransferTax>,
 }
 
 #[derive(Default, Clone, Debug, Deserialize, Eq, PartialEq, Serialize, InputObject)]
@@ -55,6 +804,16 @@ pub struct InstantiationArgument {
     pub ams_application_id: Option<ApplicationId>,
     pub proxy_application_id: Option<ApplicationId>,
     pub swap_application_id: Option<ApplicationId>,
+    // None means all block reward goes to proposer
+    pub reward_split: Option<RewardSplit>,
+    // Creator and team allocations are locked in application balance until they're vested
+    pub creator_vesting: Option<VestingSchedule>,
+    pub team_vesting: Vec<TeamAllocation>,
+    // None means pool is created when meme is created, otherwise after presale is closed
+    pub presale: Option<PresaleConfig>,
+    // Overwritten by proxy with approved genesis miners when meme is created
+    pub genesis_miners: Vec<Account>,
+    pub genesis_miner_airdrop: Option<GenesisMinerAirdrop>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -62,6 +821,8 @@ pub struct InstantiationArgument {
 pub struct MemeParameters {
     pub creator: Account,
     pub initial_liquidity: Option<Liquidity>,
+    pub pow_algorithm: HashAlgorithm,
+    pub leader_election: LeaderElectionMethod,
     pub virtual_initial_liquidity: bool,
     // TODO: work around for https://github.com/linera-io/linera-protocol/issues/3538
     pub swap_creator_chain_id: ChainId,
@@ -96,6 +857,10 @@ pub enum MemeOperation {
         to: Account,
         amount: Amount,
     },
+    // All transfers are applied in one message, or none of them is applied
+    BatchTransfer {
+        transfers: Vec<BatchTransferItem>,
+    },
     // Special operation used by swap to initialize liquidity for new pool
     InitializeLiquidity {
         to: Account,
@@ -104,17 +869,74 @@ pub enum MemeOperation {
     Approve {
         spender: Account,
         amount: Amount,
+        // None means allowance never expires
+        expires_at: Option<Timestamp>,
+    },
+    // Unspent escrow is returned to owner
+    DecreaseAllowance {
+        spender: Account,
+        amount: Amount,
+    },
+    RevokeAllowance {
+        spender: Account,
+    },
+    // Approve with permit signed by owner, could be submitted by anyone
+    Permit {
+        permit: Permit,
+        public_key: AccountPublicKey,
+        signature: AccountSignature,
     },
     TransferOwnership {
         new_owner: Account,
     },
+    // Record balances at current time, only owner could take snapshot
+    Snapshot,
+    // Only owner could update metadata, new logo and AMS listing are updated together
+    UpdateMetadata {
+        metadata: Metadata,
+    },
     Mine {
         nonce: CryptoHash,
     },
+    // Nonce only meets share difficulty of mining pool
+    SubmitShare {
+        nonce: CryptoHash,
+    },
     // Only be run on meme chain
     TransferToCaller {
         amount: Amount,
     },
+    // Called from proxy application when miner syncs its beneficiary to this meme
+    SetMinerBeneficiary {
+        miner: Account,
+        beneficiary: Option<Account>,
+    },
+    Stake {
+        amount: Amount,
+    },
+    Unstake {
+        amount: Amount,
+    },
+    // Claim unstaked balance which passed unbonding period
+    ClaimUnstaked,
+    // Claim vested creator or team allocation
+    ClaimVested,
+    // Pay native amount for whitelisted presale allocation
+    PresaleBuy {
+        amount: Amount,
+    },
+    // Close ended presale then create liquidity pool with raised funds
+    ClosePresale,
+    // Claim purchased presale tokens after release time
+    ClaimPresale,
+    Burn {
+        amount: Amount,
+    },
+    // Burn allowance approved by from
+    BurnFrom {
+        from: Account,
+        amount: Amount,
+    },
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -136,6 +958,10 @@ pub enum MemeMessage {
         to: Account,
         amount: Amount,
     },
+    BatchTransfer {
+        from: Account,
+        transfers: Vec<BatchTransferItem>,
+    },
     // Special operation used by swap to initialize liquidity for new pool
     InitializeLiquidity {
         caller: Account,
@@ -146,18 +972,514 @@ pub enum MemeMessage {
         owner: Account,
         spender: Account,
         amount: Amount,
+        expires_at: Option<Timestamp>,
+    },
+    DecreaseAllowance {
+        owner: Account,
+        spender: Account,
+        amount: Amount,
+    },
+    RevokeAllowance {
+        owner: Account,
+        spender: Account,
+    },
+    // Signature is already verified when permit operation is executed, caller is replied
+    Permit {
+        caller: Account,
+        permit: Permit,
     },
     TransferOwnership {
         owner: Account,
         new_owner: Account,
     },
+    Snapshot {
+        owner: Account,
+    },
+    UpdateMetadata {
+        owner: Account,
+        metadata: Metadata,
+    },
+    SetMinerBeneficiary {
+        caller: Account,
+        miner: Account,
+        beneficiary: Option<Account>,
+    },
+    Stake {
+        owner: Account,
+        amount: Amount,
+    },
+    Unstake {
+        owner: Account,
+        amount: Amount,
+    },
+    ClaimUnstaked {
+        owner: Account,
+    },
+    ClaimVested {
+        owner: Account,
+    },
+    PresaleBuy {
+        owner: Account,
+        amount: Amount,
+    },
+    ClaimPresale {
+        owner: Account,
+    },
+    // Sent from sender chain when PresaleBuy is bounced, paid funds are held on creation chain
+    RefundPresaleBuy {
+        owner: Account,
+        amount: Amount,
+    },
+    Burn {
+        owner: Account,
+        amount: Amount,
+    },
+    BurnFrom {
+        owner: Account,
+        from: Account,
+        amount: Amount,
+    },
+    // Replied to sender chain of tracked messages, resolve the oldest pending operation of owner
+    OperationSucceeded {
+        owner: Account,
+    },
+    OperationFailed {
+        owner: Account,
+        reason: String,
+    },
     // Mine is only run on creation chain so we don't need a message
 }
 
+// Events are only emitted on creation chain, indexers subscribe to this stream
+pub const MEME_STREAM_NAME: &str = "meme";
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq)]
+pub enum MemeEvent {
+    // Amount actually received after tax, collected tax is emitted apart as Tax
+    Transfer {
+        from: Account,
+        to: Account,
+        amount: Amount,
+    },
+    // Reflection tax is burned when there's no holder to reflect to, so destination is where the
+    // tax really goes
+    Tax {
+        from: Account,
+        to: Account,
+        amount: Amount,
+        destination: TaxDestination,
+    },
+    Approve {
+        owner: Account,
+        spender: Account,
+        amount: Amount,
+    },
+    Burn {
+        owner: Account,
+        amount: Amount,
+    },
+    // Escrow of allowance which is decreased or revoked is credited back to owner
+    DecreaseAllowance {
+        owner: Account,
+        spender: Account,
+        amount: Amount,
+    },
+    RevokeAllowance {
+        owner: Account,
+        spender: Account,
+        amount: Amount,
+    },
+    // Escrow of expired allowances is credited back to owner before its balance is used
+    ReclaimAllowance {
+        owner: Account,
+        amount: Amount,
+    },
+    // Minted block reward shares, including proposer, creator, treasury and liquidity shares
+    Mint {
+        to: Account,
+        amount: Amount,
+    },
+    // Vested airdrop is credited to miner by ClaimVested
+    Airdrop {
+        miner: Account,
+        amount: Amount,
+        vested: bool,
+    },
+    Stake {
+        owner: Account,
+        amount: Amount,
+    },
+    Unstake {
+        owner: Account,
+        amount: Amount,
+    },
+    ClaimUnstaked {
+        owner: Account,
+        amount: Amount,
+    },
+    ClaimVested {
+        owner: Account,
+        amount: Amount,
+    },
+    ClaimPresale {
+        owner: Account,
+        amount: Amount,
+    },
+    // Reward is the whole block reward before it's split
+    Mine {
+        miner: Account,
+        height: BlockHeight,
+        reward: Amount,
+    },
+    OwnershipTransferred {
+        previous_owner: Account,
+        new_owner: Account,
+    },
+    Snapshot {
+        id: u64,
+    },
+}
+
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub enum MemeResponse {
     #[default]
     Ok,
     Fail(String),
     ChainId(ChainId),
+    // Amount received by caller after transfer tax
+    Amount(Amount),
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_sdk::linera_base_types::{Account, AccountOwner, Amount, ChainId, Timestamp};
+    use std::str::FromStr;
+
+    use super::{
+        AirdropSplit, AirdropWeight, Checkpoint, EmissionPolicy, GenesisMinerAirdrop, HolderCursor,
+        Presale, PresaleConfig, RewardDecay, RewardShares, RewardSplit, TaxDestination,
+        TransferTax, Vesting, VestingRelease, VestingSchedule, WhitelistAllocation,
+    };
+
+    #[test]
+    fn test_checkpoint_value_at() {
+        let checkpoints = vec![
+            Checkpoint {
+                snapshot_id: 2,
+                value: Amount::from_tokens(10),
+            },
+            Checkpoint {
+                snapshot_id: 5,
+                value: Amount::from_tokens(20),
+            },
+        ];
+        let current = Amount::from_tokens(30);
+
+        assert_eq!(
+            Checkpoint::value_at(&checkpoints, 1, current),
+            Amount::from_tokens(10)
+        );
+        assert_eq!(
+            Checkpoint::value_at(&checkpoints, 2, current),
+            Amount::from_tokens(10)
+        );
+        assert_eq!(
+            Checkpoint::value_at(&checkpoints, 3, current),
+            Amount::from_tokens(20)
+        );
+        assert_eq!(Checkpoint::value_at(&checkpoints, 6, current), current);
+        assert_eq!(Checkpoint::value_at(&[], 1, current), current);
+    }
+
+    #[test]
+    fn test_halving_block_reward() {
+        let policy = EmissionPolicy {
+            initial_block_reward: Amount::from_tokens(50),
+            decay: RewardDecay::Halving,
+            decay_interval: 100,
+            decay_rate_bps: 0,
+            hard_cap: Amount::from_tokens(21000000),
+        };
+
+        assert_eq!(policy.block_reward(0), Amount::from_tokens(50));
+        assert_eq!(policy.block_reward(99), Amount::from_tokens(50));
+        assert_eq!(policy.block_reward(100), Amount::from_tokens(25));
+        assert_eq!(policy.block_reward(250), Amount::from_str("12.5").unwrap());
+        assert_eq!(policy.block_reward(100 * 200), Amount::ZERO);
+        assert_eq!(policy.block_reward(u64::MAX), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_exponential_block_reward() {
+        let policy = EmissionPolicy {
+            initial_block_reward: Amount::from_tokens(100),
+            decay: RewardDecay::Exponential,
+            decay_interval: 10,
+            decay_rate_bps: 1000,
+            hard_cap: Amount::from_tokens(21000000),
+        };
+
+        assert_eq!(policy.block_reward(0), Amount::from_tokens(100));
+        assert_eq!(policy.block_reward(10), Amount::from_tokens(90));
+        assert_eq!(policy.block_reward(25), Amount::from_tokens(81));
+        assert_eq!(policy.block_reward(30), Amount::from_str("72.9").unwrap());
+        assert_eq!(policy.block_reward(u64::MAX), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_split_block_reward() {
+        let split = RewardSplit {
+            proposer_bps: 7000,
+            creator_bps: 1000,
+            treasury_bps: 1000,
+            treasury: None,
+            liquidity_bps: 1000,
+        };
+
+        assert_eq!(
+            split.split(Amount::from_tokens(50)),
+            RewardShares {
+                proposer: Amount::from_tokens(35),
+                creator: Amount::from_tokens(5),
+                treasury: Amount::from_tokens(5),
+                liquidity: Amount::from_tokens(5),
+            }
+        );
+
+        // Rounding dust goes to proposer
+        let shares = split.split(Amount::from_attos(7));
+        assert_eq!(shares.proposer, Amount::from_attos(7));
+        assert_eq!(shares.liquidity, Amount::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid reward split")]
+    fn test_invalid_reward_split() {
+        RewardSplit {
+            proposer_bps: 7000,
+            creator_bps: 1000,
+            treasury_bps: 0,
+            treasury: None,
+            liquidity_bps: 1000,
+        }
+        .validate();
+    }
+
+    #[test]
+    fn test_transfer_tax() {
+        assert_eq!(
+            TransferTax::tax(Amount::from_tokens(1000), 250),
+            Amount::from_tokens(25)
+        );
+        assert_eq!(TransferTax::tax(Amount::from_attos(39), 250), Amount::ZERO);
+        assert_eq!(TransferTax::tax(Amount::ONE, 0), Amount::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid treasury")]
+    fn test_invalid_transfer_tax_treasury() {
+        TransferTax {
+            buy_bps: 100,
+            sell_bps: 200,
+            transfer_bps: 0,
+            destination: TaxDestination::Treasury,
+            treasury: None,
+        }
+        .validate();
+    }
+
+    #[test]
+    fn test_reflection() {
+        let supply = Amount::from_tokens(3);
+        let per_token = TransferTax::reflection_per_token(Amount::ONE, supply);
+        let reflection = TransferTax::reflection_of(Amount::from_tokens(2), per_token);
+
+        assert_eq!(
+            reflection,
+            Amount::from_str("0.666666666666666666").unwrap()
+        );
+        assert!(
+            reflection
+                .try_add(TransferTax::reflection_of(Amount::ONE, per_token))
+                .unwrap()
+                <= Amount::ONE
+        );
+
+        // Large reflection over large supply doesn't overflow
+        let supply = Amount::from_tokens(1_000_000_000);
+        let per_token = TransferTax::reflection_per_token(Amount::from_tokens(1_000_000), supply);
+        assert_eq!(
+            TransferTax::reflection_of(supply, per_token),
+            Amount::from_tokens(1_000_000)
+        );
+    }
+
+    #[test]
+    fn test_linear_vesting() {
+        let schedule = VestingSchedule {
+            amount: Amount::from_tokens(1000),
+            cliff_ms: 100,
+            duration_ms: 1000,
+            release: VestingRelease::Linear,
+            step_ms: 0,
+        };
+        schedule.validate();
+
+        assert_eq!(schedule.vested(99), Amount::ZERO);
+        assert_eq!(schedule.vested(100), Amount::from_tokens(100));
+        assert_eq!(schedule.vested(550), Amount::from_tokens(550));
+        assert_eq!(schedule.vested(1000), Amount::from_tokens(1000));
+        assert_eq!(schedule.vested(u64::MAX), Amount::from_tokens(1000));
+    }
+
+    #[test]
+    fn test_step_vesting() {
+        let schedule = VestingSchedule {
+            amount: Amount::from_tokens(1000),
+            cliff_ms: 0,
+            duration_ms: 1000,
+            release: VestingRelease::Step,
+            step_ms: 250,
+        };
+        schedule.validate();
+
+        assert_eq!(schedule.vested(249), Amount::ZERO);
+        assert_eq!(schedule.vested(250), Amount::from_tokens(250));
+        assert_eq!(schedule.vested(749), Amount::from_tokens(500));
+        assert_eq!(schedule.vested(1000), Amount::from_tokens(1000));
+
+        // Claimed amount is not claimable again
+        let vesting = Vesting {
+            schedule,
+            started_at: Timestamp::from(0),
+            claimed: Amount::from_tokens(250),
+        };
+        assert_eq!(
+            vesting.claimable(Timestamp::from(500_000)),
+            Amount::from_tokens(250)
+        );
+        assert_eq!(vesting.claimable(Timestamp::from(0)), Amount::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid vesting step")]
+    fn test_invalid_vesting_step() {
+        VestingSchedule {
+            amount: Amount::from_tokens(1000),
+            cliff_ms: 0,
+            duration_ms: 1000,
+            release: VestingRelease::Step,
+            step_ms: 0,
+        }
+        .validate();
+    }
+
+    #[test]
+    fn test_presale_tokens() {
+        let chain_id =
+            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+                .unwrap();
+        let config = PresaleConfig {
+            whitelist: vec![WhitelistAllocation {
+                account: Account::chain(chain_id),
+                cap: Amount::from_tokens(1000),
+            }],
+            price: Amount::from_millis(10),
+            ends_at: Timestamp::from(1000),
+            release_at: Timestamp::from(2000),
+        };
+        config.validate();
+
+        let presale = Presale {
+            price: config.price,
+            ends_at: config.ends_at,
+            release_at: config.release_at,
+            reserved: Amount::from_tokens(1000),
+            sold: Amount::ZERO,
+            raised: Amount::ZERO,
+            closed: false,
+        };
+        assert_eq!(
+            presale.tokens_for(Amount::from_tokens(1)),
+            Amount::from_tokens(100)
+        );
+        assert_eq!(
+            presale.tokens_for(Amount::from_attos(1)),
+            Amount::from_attos(100)
+        );
+    }
+
+    #[test]
+    fn test_genesis_miner_airdrop_shares() {
+        let chain_id =
+            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+                .unwrap();
+        let miner = |index: u8| Account {
+            chain_id,
+            owner: AccountOwner::from_str(&format!("0x{}", format!("{:02x}", index).repeat(32)))
+                .unwrap(),
+        };
+        let miners = [miner(1), miner(2), miner(3)];
+
+        let mut airdrop = GenesisMinerAirdrop {
+            amount: Amount::from_tokens(300),
+            split: AirdropSplit::Equal,
+            weights: vec![],
+            vesting: None,
+        };
+        airdrop.validate();
+        assert_eq!(
+            airdrop.shares(&miners),
+            miners
+                .iter()
+                .map(|miner| (*miner, Amount::from_tokens(100)))
+                .collect::<Vec<_>>()
+        );
+
+        airdrop.split = AirdropSplit::Weighted;
+        airdrop.weights = vec![
+            AirdropWeight {
+                miner: miner(1),
+                weight: 2,
+            },
+            AirdropWeight {
+                miner: miner(2),
+                weight: 1,
+            },
+            // Not a genesis miner
+            AirdropWeight {
+                miner: miner(4),
+                weight: 3,
+            },
+        ];
+        airdrop.validate();
+        assert_eq!(
+            airdrop.shares(&miners),
+            vec![
+                (miner(1), Amount::from_tokens(200)),
+                (miner(2), Amount::from_tokens(100))
+            ]
+        );
+
+        assert_eq!(airdrop.shares(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_holder_cursor() {
+        let cursor = HolderCursor {
+            account: Account {
+                chain_id: ChainId::from_str(
+                    "aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8",
+                )
+                .unwrap(),
+                owner: AccountOwner::from_str(&format!("0x{}", "05".repeat(32))).unwrap(),
+            },
+            balance: Amount::from_tokens(12),
+        };
+
+        assert_eq!(HolderCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(HolderCursor::decode("0"), None);
+        assert_eq!(HolderCursor::decode("zz"), None);
+        assert_eq!(HolderCursor::decode(&cursor.encode()[2..]), None);
+    }
 }
@@ -0,0 +1,256 @@
+use crate::meme::HashAlgorithm;
+use linera_sdk::{
+    bcs,
+    linera_base_types::{Account, BlockHeight, ChainId, CryptoHash},
+};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+
+/// Hash function used to verify mined nonce. Contract and off-chain miners share the same
+/// implementation so a found nonce is always accepted by the meme application.
+pub trait PowAlgorithm {
+    fn hash(&self, data: &[u8]) -> [u8; 32];
+}
+
+pub struct Sha256d;
+
+impl PowAlgorithm for Sha256d {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        Sha256::digest(Sha256::digest(data)).into()
+    }
+}
+
+pub struct Keccak;
+
+impl PowAlgorithm for Keccak {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        Keccak256::digest(data).into()
+    }
+}
+
+pub struct Blake3;
+
+impl PowAlgorithm for Blake3 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        *blake3::hash(data).as_bytes()
+    }
+}
+
+/// Memory-hard hash. Parameters are kept small enough to be verified in contract.
+pub struct Scrypt;
+
+impl Scrypt {
+    const LOG_N: u8 = 10;
+    const R: u32 = 8;
+    const P: u32 = 1;
+    const SALT: &'static [u8] = b"linera-meme-pow";
+}
+
+impl PowAlgorithm for Scrypt {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        let params =
+            scrypt::Params::new(Self::LOG_N, Self::R, Self::P, 32).expect("Invalid scrypt params");
+        let mut output = [0u8; 32];
+        scrypt::scrypt(data, Self::SALT, &params, &mut output).expect("Failed scrypt");
+        output
+    }
+}
+
+impl PowAlgorithm for HashAlgorithm {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256d => Sha256d.hash(data),
+            HashAlgorithm::Keccak256 => Keccak.hash(data),
+            HashAlgorithm::Blake3 => Blake3.hash(data),
+            HashAlgorithm::Scrypt => Scrypt.hash(data),
+        }
+    }
+}
+
+/// Everything a nonce is bound to. A nonce found for one challenge cannot be replayed on another
+/// chain, at another height, on top of another previous hash or by another miner.
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq)]
+pub struct MiningChallenge {
+    pub chain_id: ChainId,
+    pub height: BlockHeight,
+    pub previous_hash: CryptoHash,
+    pub miner: Account,
+}
+
+impl MiningChallenge {
+    pub fn hash(&self, algorithm: &impl PowAlgorithm, nonce: CryptoHash) -> CryptoHash {
+        let bytes = bcs::to_bytes(&(self, nonce)).expect("Failed serialize challenge");
+        to_crypto_hash(algorithm.hash(&bytes))
+    }
+
+    pub fn verify(
+        &self,
+        algorithm: &impl PowAlgorithm,
+        nonce: CryptoHash,
+        difficulty: u64,
+    ) -> bool {
+        meets_difficulty(self.hash(algorithm, nonce), difficulty)
+    }
+}
+
+// Runtime doesn't expose previous block hash, so the first challenge of a chain is seeded from
+// its chain id, then every accepted hash becomes previous hash of the next one
+pub fn genesis_hash(chain_id: ChainId) -> CryptoHash {
+    let bytes = bcs::to_bytes(&chain_id).expect("Failed serialize chain id");
+    to_crypto_hash(Keccak256::digest(&bytes).into())
+}
+
+// Difficulty could be at most multiplied or divided by this factor at each retarget
+pub const MAX_DIFFICULTY_ADJUSTMENT: u64 = 4;
+
+pub fn retarget_difficulty(difficulty: u64, expected_micros: u64, actual_micros: u64) -> u64 {
+    let expected_micros = expected_micros.max(1);
+    let actual_micros = actual_micros.clamp(
+        (expected_micros / MAX_DIFFICULTY_ADJUSTMENT).max(1),
+        expected_micros.saturating_mul(MAX_DIFFICULTY_ADJUSTMENT),
+    );
+    let difficulty =
+        u128::from(difficulty) * u128::from(expected_micros) / u128::from(actual_micros);
+    u64::try_from(difficulty).unwrap_or(u64::MAX).max(1)
+}
+
+pub fn target(difficulty: u64) -> U256 {
+    U256::MAX / U256::from(difficulty.max(1))
+}
+
+pub fn meets_difficulty(hash: CryptoHash, difficulty: u64) -> bool {
+    U256::from_big_endian(hash.as_bytes().as_slice()) <= target(difficulty)
+}
+
+// Hash bytes are big endian words of CryptoHash
+pub(crate) fn to_crypto_hash(bytes: [u8; 32]) -> CryptoHash {
+    let mut words = [0u64; 4];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+        *word = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    CryptoHash::from(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_sdk::linera_base_types::{
+        Account, AccountOwner, BlockHeight, ChainId, CryptoHash, TestString,
+    };
+    use std::str::FromStr;
+
+    use super::{
+        genesis_hash, meets_difficulty, retarget_difficulty, to_crypto_hash, Keccak,
+        MiningChallenge, PowAlgorithm,
+    };
+    use crate::meme::HashAlgorithm;
+
+    fn challenge() -> MiningChallenge {
+        let chain_id =
+            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+                .unwrap();
+        let owner = AccountOwner::from_str(
+            "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc7f",
+        )
+        .unwrap();
+
+        MiningChallenge {
+            chain_id,
+            height: BlockHeight::from(1),
+            previous_hash: genesis_hash(chain_id),
+            miner: Account { chain_id, owner },
+        }
+    }
+
+    #[test]
+    fn test_to_crypto_hash() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0x12;
+        bytes[31] = 0xab;
+        let hash = to_crypto_hash(bytes);
+
+        assert_eq!(hash.as_bytes().as_slice(), bytes.as_slice());
+        assert_eq!(
+            hash,
+            CryptoHash::from_str(
+                "12000000000000000000000000000000000000000000000000000000000000ab"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_minimum_difficulty() {
+        let challenge = challenge();
+        let nonce = CryptoHash::new(&TestString::new("nonce"));
+
+        assert!(challenge.verify(&Keccak, nonce, 0));
+        assert!(challenge.verify(&Keccak, nonce, 1));
+        assert!(!challenge.verify(&Keccak, nonce, u64::MAX));
+    }
+
+    #[test]
+    fn test_mine_nonce() {
+        let challenge = challenge();
+        let difficulty = 256;
+
+        let nonce = (0..100000)
+            .map(|i| CryptoHash::new(&TestString::new(format!("nonce {}", i))))
+            .find(|nonce| challenge.verify(&Keccak, *nonce, difficulty))
+            .expect("Failed mine nonce");
+
+        let hash = challenge.hash(&Keccak, nonce);
+        assert!(meets_difficulty(hash, difficulty));
+        assert_eq!(hash, challenge.hash(&Keccak, nonce));
+
+        // Nonce is bound to miner and height
+        let mut other = challenge.clone();
+        other.height = BlockHeight::from(2);
+        assert_ne!(hash, other.hash(&Keccak, nonce));
+    }
+
+    #[test]
+    fn test_hash_algorithms() {
+        let challenge = challenge();
+        let nonce = CryptoHash::new(&TestString::new("nonce"));
+        let algorithms = [
+            HashAlgorithm::Sha256d,
+            HashAlgorithm::Keccak256,
+            HashAlgorithm::Blake3,
+            HashAlgorithm::Scrypt,
+        ];
+
+        let hashes = algorithms
+            .iter()
+            .map(|algorithm| challenge.hash(algorithm, nonce))
+            .collect::<Vec<_>>();
+
+        for (i, algorithm) in algorithms.iter().enumerate() {
+            // Hash is deterministic
+            assert_eq!(hashes[i], challenge.hash(algorithm, nonce));
+            // Nonce found with one algorithm is not valid for another one
+            for j in i + 1..algorithms.len() {
+                assert_ne!(hashes[i], hashes[j]);
+            }
+        }
+
+        assert_eq!(HashAlgorithm::Keccak256.hash(b"meme"), Keccak.hash(b"meme"));
+    }
+
+    #[test]
+    fn test_retarget_difficulty() {
+        // Blocks are mined at expected speed
+        assert_eq!(retarget_difficulty(1000, 600, 600), 1000);
+        // Blocks are mined two times faster than expected
+        assert_eq!(retarget_difficulty(1000, 600, 300), 2000);
+        // Blocks are mined two times slower than expected
+        assert_eq!(retarget_difficulty(1000, 600, 1200), 500);
+        // Adjustment is clamped
+        assert_eq!(retarget_difficulty(1000, 600, 0), 4000);
+        assert_eq!(retarget_difficulty(1000, 600, 60000), 250);
+        // Difficulty never goes to zero
+        assert_eq!(retarget_difficulty(1, 600, 60000), 1);
+        assert_eq!(retarget_difficulty(u64::MAX, 600, 1), u64::MAX);
+    }
+}
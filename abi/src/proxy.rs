@@ -31,6 +31,8 @@ impl ServiceAbi for ProxyAbi {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Miner {
     pub owner: Account,
+    // Mining reward will be paid to beneficiary if it's set
+    pub beneficiary: Option<Account>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -67,6 +69,13 @@ pub enum ProxyOperation {
     // Miner can only register from their client
     RegisterMiner,
     DeregisterMiner,
+    SetMinerBeneficiary {
+        beneficiary: Account,
+    },
+    // Beneficiary is propagated to meme of chain_id lazily when miner syncs it
+    SyncMinerBeneficiary {
+        chain_id: ChainId,
+    },
 
     CreateMeme {
         meme_instantiation_argument: MemeInstantiationArgument,
@@ -114,6 +123,14 @@ pub enum ProxyMessage {
     DeregisterMiner {
         owner: Account,
     },
+    SetMinerBeneficiary {
+        owner: Account,
+        beneficiary: Account,
+    },
+    SyncMinerBeneficiary {
+        owner: Account,
+        chain_id: ChainId,
+    },
 
     CreateMeme {
         instantiation_argument: MemeInstantiationArgument,
@@ -54,6 +54,10 @@ pub enum PoolOperation {
         to: Option<Account>,
         block_timestamp: Option<Timestamp>,
     },
+    // Add token 0 to reserve without minting shares, called from router application
+    TopUpReserve {
+        amount_0: Amount,
+    },
 }
 
 #[derive(Debug, Deserialize, Serialize, Default)]
@@ -73,6 +77,8 @@ pub enum PoolMessage {
     // Sent from meme chain to user chain
     FundSuccess {
         transfer_id: u64,
+        // Less than requested amount if meme transfer is taxed
+        amount: Amount,
     },
     FundFail {
         transfer_id: u64,
@@ -118,6 +124,10 @@ pub enum PoolMessage {
     NewTransaction {
         transaction: Transaction,
     },
+    TopUpReserve {
+        caller: ApplicationId,
+        amount_0: Amount,
+    },
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -129,6 +139,9 @@ pub struct PoolParameters {
     // TODO: work around of https://github.com/linera-io/linera-protocol/issues/3538
     pub token_0_creator_chain_id: ChainId,
     pub token_1_creator_chain_id: Option<ChainId>,
+    // Transfer tax rate charged by token 0 on meme sent out of its native pool, only known when
+    // pool is initialized by meme itself
+    pub token_0_buy_bps: u16,
 }
 
 scalar!(PoolParameters);
@@ -38,6 +38,8 @@ pub enum SwapOperation {
         // Only for creator to initialize pool
         virtual_liquidity: bool,
         to: Option<Account>,
+        // Transfer tax rate of meme bought from its native pool
+        buy_bps: u16,
     },
     // User can only create meme meme pair. Meme native pair is created by creator
     CreatePool {
@@ -51,6 +53,14 @@ pub enum SwapOperation {
         amount_1: Amount,
         to: Option<Account>,
     },
+    // Add mined meme to reserve of meme native pool, called from meme creation chain
+    TopUpLiquidity {
+        // TODO: use to avoid reentrant invocation before
+        // https://github.com/linera-io/linera-protocol/issues/3538 being fixed
+        token_0_creator_chain_id: ChainId,
+        token_0: ApplicationId,
+        amount_0: Amount,
+    },
     // Notify swap of new transaction, called from pool chain
     UpdatePool {
         token_0: ApplicationId,
@@ -83,6 +93,7 @@ pub enum SwapMessage {
         // Only for creator to initialize pool
         virtual_liquidity: bool,
         to: Option<Account>,
+        buy_bps: u16,
     },
     CreatePool {
         creator: Account,
@@ -98,6 +109,7 @@ pub enum SwapMessage {
         virtual_initial_liquidity: bool,
         to: Option<Account>,
         user_pool: bool,
+        token_0_buy_bps: u16,
     },
     PoolCreated {
         creator: Account,
@@ -141,6 +153,11 @@ pub enum SwapMessage {
         reserve_0: Amount,
         reserve_1: Amount,
     },
+    // Execute on swap creation chain
+    TopUpLiquidity {
+        token_0: ApplicationId,
+        amount_0: Amount,
+    },
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, SimpleObject)]
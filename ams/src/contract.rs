@@ -151,11 +151,27 @@ impl ApplicationContract {
     }
 
     async fn on_op_update(
-        &self,
-        _application_id: ApplicationId,
-        _metadata: Metadata,
+        &mut self,
+        application_id: ApplicationId,
+        metadata: Metadata,
     ) -> Result<AmsResponse, AmsError> {
-        Err(AmsError::NotImplemented)
+        // Application updates its own listing when it calls us
+        let owner = match self.runtime.authenticated_caller_id() {
+            Some(caller_id) => Account {
+                chain_id: self.runtime.chain_id(),
+                owner: AccountOwner::from(caller_id),
+            },
+            None => self.owner_account(),
+        };
+        self.runtime
+            .prepare_message(AmsMessage::Update {
+                owner,
+                application_id,
+                metadata,
+            })
+            .with_authentication()
+            .send_to(self.runtime.application_creator_chain_id());
+        Ok(AmsResponse::Ok)
     }
 
     async fn on_msg_register(&mut self, metadata: Metadata) -> Result<(), AmsError> {
@@ -180,10 +196,254 @@ impl ApplicationContract {
 
     async fn on_msg_update(
         &mut self,
-        _owner: Account,
-        _application_id: ApplicationId,
-        _metadata: Metadata,
+        owner: Account,
+        application_id: ApplicationId,
+        metadata: Metadata,
     ) -> Result<(), AmsError> {
-        Err(AmsError::NotImplemented)
+        self.state
+            .update_application(owner, application_id, metadata)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abi::{
+        ams::{AmsAbi, AmsMessage, AmsOperation, InstantiationArgument, Metadata, MEME},
+        store_type::StoreType,
+    };
+    use linera_sdk::{
+        linera_base_types::{
+            Account, AccountOwner, ApplicationId, ChainId, CryptoHash, TestString, Timestamp,
+        },
+        util::BlockingWait,
+        views::View,
+        Contract, ContractRuntime,
+    };
+    use std::str::FromStr;
+
+    use super::{AmsState, ApplicationContract};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_update_by_creator() {
+        let mut ams = create_and_instantiate_ams().await;
+        let creator = creator();
+        let application_id = meme_application_id();
+
+        ams.execute_message(AmsMessage::Register {
+            metadata: metadata(creator, application_id),
+        })
+        .await;
+
+        let mut metadata = metadata(other_user(), application_id);
+        metadata.description = "Updated description".to_string();
+        metadata.telegram = Some("https://t.me/meme".to_string());
+        metadata.created_at = Timestamp::from(2_000_000);
+        ams.execute_message(AmsMessage::Update {
+            owner: creator,
+            application_id,
+            metadata,
+        })
+        .await;
+
+        // Listing owner and creation time could not be changed by update
+        let application = ams
+            .state
+            .applications
+            .get(&application_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(application.description, "Updated description");
+        assert_eq!(application.telegram, Some("https://t.me/meme".to_string()));
+        assert_eq!(application.creator, creator);
+        assert_eq!(application.created_at, Timestamp::from(1_000_000));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_update_by_application() {
+        let mut ams = create_and_instantiate_ams().await;
+        let application_id = meme_application_id();
+
+        ams.execute_message(AmsMessage::Register {
+            metadata: metadata(creator(), application_id),
+        })
+        .await;
+
+        let mut metadata = metadata(creator(), application_id);
+        metadata.description = "Updated by application".to_string();
+        ams.execute_message(AmsMessage::Update {
+            owner: Account {
+                chain_id: chain_id(),
+                owner: AccountOwner::from(application_id),
+            },
+            application_id,
+            metadata,
+        })
+        .await;
+
+        let application = ams
+            .state
+            .applications
+            .get(&application_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(application.description, "Updated by application");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed MSG: update: PermissionDenied")]
+    async fn message_update_permission_denied() {
+        let mut ams = create_and_instantiate_ams().await;
+        let application_id = meme_application_id();
+
+        ams.execute_message(AmsMessage::Register {
+            metadata: metadata(creator(), application_id),
+        })
+        .await;
+        ams.execute_message(AmsMessage::Update {
+            owner: other_user(),
+            application_id,
+            metadata: metadata(other_user(), application_id),
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed MSG: update: InvalidApplicationType")]
+    async fn message_update_invalid_application_type() {
+        let mut ams = create_and_instantiate_ams().await;
+        let application_id = meme_application_id();
+
+        ams.execute_message(AmsMessage::Register {
+            metadata: metadata(creator(), application_id),
+        })
+        .await;
+
+        let mut metadata = metadata(creator(), application_id);
+        metadata.application_type = "Unknown".to_string();
+        ams.execute_message(AmsMessage::Update {
+            owner: creator(),
+            application_id,
+            metadata,
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed MSG: update: NotExists")]
+    async fn message_update_not_registered() {
+        let mut ams = create_and_instantiate_ams().await;
+        let application_id = meme_application_id();
+
+        ams.execute_message(AmsMessage::Update {
+            owner: creator(),
+            application_id,
+            metadata: metadata(creator(), application_id),
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn operation_update_from_application() {
+        let mut ams = create_and_instantiate_ams().await;
+        let application_id = meme_application_id();
+
+        // Application called by its own chain updates listing as itself
+        ams.runtime
+            .set_authenticated_caller_id(Some(application_id));
+        ams.execute_operation(AmsOperation::Update {
+            application_id,
+            metadata: metadata(creator(), application_id),
+        })
+        .await;
+
+        let owner = Account {
+            chain_id: chain_id(),
+            owner: AccountOwner::from(application_id),
+        };
+        assert!(matches!(
+            &ams.runtime.created_send_message_requests().last().unwrap().message,
+            AmsMessage::Update { owner: sender, .. } if *sender == owner
+        ));
+    }
+
+    fn chain_id() -> ChainId {
+        ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+            .unwrap()
+    }
+
+    fn creator() -> Account {
+        Account {
+            chain_id: chain_id(),
+            owner: AccountOwner::from_str(
+                "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e01",
+            )
+            .unwrap(),
+        }
+    }
+
+    fn other_user() -> Account {
+        Account {
+            chain_id: chain_id(),
+            owner: AccountOwner::from_str(
+                "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e02",
+            )
+            .unwrap(),
+        }
+    }
+
+    fn meme_application_id() -> ApplicationId {
+        ApplicationId::from_str("b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bae")
+            .unwrap()
+    }
+
+    fn metadata(creator: Account, application_id: ApplicationId) -> Metadata {
+        Metadata {
+            creator,
+            application_name: "Test Token".to_string(),
+            application_id,
+            application_type: MEME.to_string(),
+            key_words: vec![],
+            logo_store_type: StoreType::S3,
+            logo: CryptoHash::new(&TestString::new("Test Logo")),
+            description: "Test token description".to_string(),
+            twitter: None,
+            telegram: None,
+            discord: None,
+            website: None,
+            github: None,
+            spec: None,
+            created_at: Timestamp::from(1_000_000),
+        }
+    }
+
+    async fn create_and_instantiate_ams() -> ApplicationContract {
+        let operator = AccountOwner::from_str(
+            "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e00",
+        )
+        .unwrap();
+        let application_id = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bad",
+        )
+        .unwrap()
+        .with_abi::<AmsAbi>();
+        let runtime = ContractRuntime::new()
+            .with_application_parameters(())
+            .with_authenticated_signer(operator)
+            .with_authenticated_caller_id(None)
+            .with_chain_id(chain_id())
+            .with_application_creator_chain_id(chain_id())
+            .with_application_id(application_id);
+        let mut contract = ApplicationContract {
+            state: AmsState::load(runtime.root_view_storage_context())
+                .blocking_wait()
+                .expect("Failed to read from mock key value store"),
+            runtime,
+        };
+
+        contract.instantiate(InstantiationArgument {}).await;
+        contract
     }
 }
@@ -14,4 +14,10 @@ pub enum AmsError {
 
     #[error("Already exists")]
     AlreadyExists,
+
+    #[error("Not exists")]
+    NotExists,
+
+    #[error("Invalid application type")]
+    InvalidApplicationType,
 }
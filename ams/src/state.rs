@@ -2,7 +2,7 @@ use abi::ams::{Metadata, APPLICATION_TYPES};
 use ams::AmsError;
 use async_graphql::SimpleObject;
 use linera_sdk::{
-    linera_base_types::{Account, ApplicationId},
+    linera_base_types::{Account, AccountOwner, ApplicationId},
     views::{linera_views, MapView, QueueView, RegisterView, RootView, ViewStorageContext},
 };
 
@@ -52,4 +52,33 @@ impl AmsState {
         let application_id = application.application_id;
         Ok(self.applications.insert(&application_id, application)?)
     }
+
+    // Listing could be updated by its creator, or by the registered application itself
+    pub(crate) async fn update_application(
+        &mut self,
+        owner: Account,
+        application_id: ApplicationId,
+        mut metadata: Metadata,
+    ) -> Result<(), AmsError> {
+        let Some(application) = self.applications.get(&application_id).await? else {
+            return Err(AmsError::NotExists);
+        };
+        if owner != application.creator && owner.owner != AccountOwner::from(application_id) {
+            return Err(AmsError::PermissionDenied);
+        }
+        // Types are preset from APPLICATION_TYPES, then added by operator
+        if !self
+            .application_types
+            .elements()
+            .await?
+            .contains(&metadata.application_type)
+        {
+            return Err(AmsError::InvalidApplicationType);
+        }
+
+        metadata.creator = application.creator;
+        metadata.application_id = application_id;
+        metadata.created_at = application.created_at;
+        Ok(self.applications.insert(&application_id, metadata)?)
+    }
 }
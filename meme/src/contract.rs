@@ -8,15 +8,23 @@ mod state;
 use abi::{
     ams::{AmsAbi, AmsOperation, Metadata, MEME},
     blob_gateway::{BlobDataType, BlobGatewayAbi, BlobGatewayOperation},
+    election::LeaderElectionMethod,
     meme::{
-        InstantiationArgument, Liquidity, MemeAbi, MemeMessage, MemeOperation, MemeParameters,
-        MemeResponse,
+        AirdropSplit, BatchTransferItem, InstantiationArgument, Liquidity, MemeAbi, MemeEvent,
+        MemeMessage, MemeOperation, MemeParameters, MemeResponse, Metadata as MemeMetadata,
+        PendingOperationKind, PendingOperationStatus, Permit, TaxDestination, MEME_STREAM_NAME,
     },
     policy::open_chain_fee_budget,
-    swap::router::{SwapAbi, SwapOperation},
+    swap::{
+        pool::{PoolAbi, PoolOperation},
+        router::{SwapAbi, SwapOperation},
+    },
 };
 use linera_sdk::{
-    linera_base_types::{Account, AccountOwner, Amount, ChainId, CryptoHash, WithContractAbi},
+    linera_base_types::{
+        Account, AccountOwner, AccountPublicKey, AccountSignature, Amount, ApplicationId, ChainId,
+        CryptoHash, StreamName, Timestamp, WithContractAbi,
+    },
     views::{RootView, View},
     Contract, ContractRuntime,
 };
@@ -27,6 +35,9 @@ use self::state::MemeState;
 pub struct MemeContract {
     state: MemeState,
     runtime: ContractRuntime<Self>,
+    // Mock runtime doesn't expose emitted events, they're kept for assertions
+    #[cfg(test)]
+    events: Vec<MemeEvent>,
 }
 
 linera_sdk::contract!(MemeContract);
@@ -39,13 +50,18 @@ impl Contract for MemeContract {
     type Message = MemeMessage;
     type InstantiationArgument = InstantiationArgument;
     type Parameters = MemeParameters;
-    type EventValue = ();
+    type EventValue = MemeEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
         let state = MemeState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load state");
-        MemeContract { state, runtime }
+        MemeContract {
+            state,
+            runtime,
+            #[cfg(test)]
+            events: Vec::new(),
+        }
     }
 
     async fn instantiate(&mut self, mut instantiation_argument: InstantiationArgument) {
@@ -59,19 +75,77 @@ impl Contract for MemeContract {
         let creator = self.creator();
         let application = self.application_account();
 
+        // Proof of stake chain must allow holders to stake
+        assert!(
+            self.runtime.application_parameters().leader_election
+                != LeaderElectionMethod::ProofOfStake
+                || instantiation_argument.meme.staking.is_some(),
+            "Invalid staking"
+        );
+
         instantiation_argument.meme.virtual_initial_liquidity = self.virtual_initial_liquidity();
         instantiation_argument.meme.initial_liquidity = self.initial_liquidity();
+        instantiation_argument.meme.mining.pow_algorithm =
+            self.runtime.application_parameters().pow_algorithm;
+
+        let creator_vesting = instantiation_argument.creator_vesting.take();
+        let team_vesting = std::mem::take(&mut instantiation_argument.team_vesting);
+        let presale = instantiation_argument.presale.take();
+        let airdrop = instantiation_argument.genesis_miner_airdrop.take();
+        let vested = airdrop
+            .as_ref()
+            .is_some_and(|airdrop| airdrop.split == AirdropSplit::Vested);
+        // Genesis miners are approved by proxy, they're ignored if meme isn't created by proxy
+        let genesis_miners = std::mem::take(&mut instantiation_argument.genesis_miners);
+        let genesis_miners = if instantiation_argument.proxy_application_id.is_some()
+            && self.runtime.authenticated_caller_id() == instantiation_argument.proxy_application_id
+        {
+            genesis_miners
+        } else {
+            Vec::new()
+        };
+
+        // Raised presale funds are real native tokens added to a real initial liquidity pool
+        assert!(
+            presale.is_none()
+                || (!self.virtual_initial_liquidity()
+                    && self.initial_liquidity().is_some()
+                    && instantiation_argument.swap_application_id.is_some()),
+            "Invalid presale"
+        );
 
         self.state
             .instantiate(creator, application, instantiation_argument)
             .await
             .expect("Failed instantiate");
 
-        // Let creator hold one hundred tokens for easy test
+        let method = self.runtime.application_parameters().leader_election;
+        let height = self.runtime.block_height();
+        let timestamp = self.runtime.system_time();
+        self.state
+            .initialize_leader_election(method, height, timestamp);
+
+        // Creator and team allocations are locked before initial liquidity is approved
+        self.state
+            .initialize_vesting(creator, creator_vesting, team_vesting, timestamp)
+            .await
+            .expect("Failed initialize vesting");
         self.state
-            .initialize_balance(creator, self.state.initial_owner_balance().await)
+            .initialize_presale(presale)
+            .await
+            .expect("Failed initialize presale");
+        let shares = self
+            .state
+            .initialize_airdrop(airdrop, genesis_miners, timestamp)
             .await
-            .expect("Failed initialize balance");
+            .expect("Failed initialize airdrop");
+        for (miner, amount) in shares {
+            self.emit(MemeEvent::Airdrop {
+                miner,
+                amount,
+                vested,
+            });
+        }
 
         if let Some(liquidity) = self.initial_liquidity() {
             let swap_creator_chain = self.swap_creator_chain_id();
@@ -85,48 +159,150 @@ impl Contract for MemeContract {
         self.register_logo().await;
 
         // When the meme application is created, initial liquidity allowance should already be approved
-        self.create_liquidity_pool()
-            .await
-            .expect("Failed create liquidity pool");
+        // If there is a presale, pool will be created after presale is closed
+        if self.state.presale().is_none() {
+            self.create_liquidity_pool()
+                .await
+                .expect("Failed create liquidity pool");
+        } else {
+            self.hold_presale_liquidity();
+        }
     }
 
     async fn execute_operation(&mut self, operation: MemeOperation) -> MemeResponse {
         if !self.operation_executable(&operation) {
             panic!("Operations must be run on right chain");
         }
+        // Only operations proposed directly by owners of meme chain are checked, calls from other
+        // applications are authenticated by the message signer but not the block proposer
+        if self.runtime.chain_id() == self.runtime.application_creator_chain_id()
+            && self.runtime.authenticated_caller_id().is_none()
+        {
+            let mining = matches!(operation, MemeOperation::Mine { .. });
+            let sharing = matches!(operation, MemeOperation::SubmitShare { .. });
+            self.elect_proposer(mining, sharing)
+                .await
+                .expect("Failed OP: elect proposer");
+        }
 
         match operation {
             MemeOperation::Transfer { to, amount } => self
                 .on_op_transfer(to, amount)
+                .await
                 .expect("Failed OP: transfer"),
             MemeOperation::TransferFrom { from, to, amount } => self
                 .on_op_transfer_from(from, to, amount)
+                .await
                 .expect("Failed OP: trasnfer from"),
             MemeOperation::TransferFromApplication { to, amount } => self
                 .on_op_transfer_from_application(to, amount)
                 .await
                 .expect("Failed OP: trasnfer from application"),
+            MemeOperation::BatchTransfer { transfers } => self
+                .on_op_batch_transfer(transfers)
+                .await
+                .expect("Failed OP: batch transfer"),
             MemeOperation::InitializeLiquidity { to, amount } => self
                 .on_op_initialize_liquidity(to, amount)
                 .await
                 .expect("Failed OP: initialize liquidity"),
-            MemeOperation::Approve { spender, amount } => self
-                .on_op_approve(spender, amount)
+            MemeOperation::Approve {
+                spender,
+                amount,
+                expires_at,
+            } => self
+                .on_op_approve(spender, amount, expires_at)
+                .await
                 .expect("Failed OP: approve"),
+            MemeOperation::DecreaseAllowance { spender, amount } => self
+                .on_op_decrease_allowance(spender, amount)
+                .await
+                .expect("Failed OP: decrease allowance"),
+            MemeOperation::RevokeAllowance { spender } => self
+                .on_op_revoke_allowance(spender)
+                .await
+                .expect("Failed OP: revoke allowance"),
             MemeOperation::TransferOwnership { new_owner } => self
                 .on_op_transfer_ownership(new_owner)
+                .await
                 .expect("Failed OP: transfer ownership"),
+            MemeOperation::Snapshot => self.on_op_snapshot().await.expect("Failed OP: snapshot"),
+            MemeOperation::UpdateMetadata { metadata } => self
+                .on_op_update_metadata(metadata)
+                .await
+                .expect("Failed OP: update metadata"),
             MemeOperation::TransferToCaller { amount } => self
                 .on_op_transfer_to_caller(amount)
                 .await
                 .expect("Failed OP: transfer to caller"),
-            MemeOperation::Mine { nonce } => self.on_op_mine(nonce).expect("Failed OP: mine"),
+            MemeOperation::Mine { nonce } => self.on_op_mine(nonce).await.expect("Failed OP: mine"),
+            MemeOperation::SubmitShare { nonce } => self
+                .on_op_submit_share(nonce)
+                .await
+                .expect("Failed OP: submit share"),
+            MemeOperation::SetMinerBeneficiary { miner, beneficiary } => self
+                .on_op_set_miner_beneficiary(miner, beneficiary)
+                .expect("Failed OP: set miner beneficiary"),
+            MemeOperation::Stake { amount } => {
+                self.on_op_stake(amount).await.expect("Failed OP: stake")
+            }
+            MemeOperation::Unstake { amount } => self
+                .on_op_unstake(amount)
+                .await
+                .expect("Failed OP: unstake"),
+            MemeOperation::ClaimUnstaked => self
+                .on_op_claim_unstaked()
+                .await
+                .expect("Failed OP: claim unstaked"),
+            MemeOperation::Burn { amount } => {
+                self.on_op_burn(amount).await.expect("Failed OP: burn")
+            }
+            MemeOperation::BurnFrom { from, amount } => self
+                .on_op_burn_from(from, amount)
+                .await
+                .expect("Failed OP: burn from"),
+            MemeOperation::ClaimVested => self
+                .on_op_claim_vested()
+                .await
+                .expect("Failed OP: claim vested"),
+            MemeOperation::PresaleBuy { amount } => self
+                .on_op_presale_buy(amount)
+                .await
+                .expect("Failed OP: presale buy"),
+            MemeOperation::ClosePresale => self
+                .on_op_close_presale()
+                .await
+                .expect("Failed OP: close presale"),
+            MemeOperation::ClaimPresale => self
+                .on_op_claim_presale()
+                .await
+                .expect("Failed OP: claim presale"),
+            MemeOperation::Permit {
+                permit,
+                public_key,
+                signature,
+            } => self
+                .on_op_permit(permit, public_key, signature)
+                .await
+                .expect("Failed OP: permit"),
         }
     }
 
     async fn execute_message(&mut self, message: MemeMessage) {
-        // All messages must be run on creation chain side
-        if self.runtime.chain_id() != self.runtime.application_creator_chain_id() {
+        // Tracked messages rejected by creation chain are bounced back to sender chain
+        if self.runtime.message_is_bouncing() == Some(true) {
+            self.on_msg_bounced(message)
+                .await
+                .expect("Failed MSG: bounced");
+            return;
+        }
+
+        // All messages except replies must be run on creation chain side
+        let reply = matches!(
+            message,
+            MemeMessage::OperationSucceeded { .. } | MemeMessage::OperationFailed { .. }
+        );
+        if !reply && self.runtime.chain_id() != self.runtime.application_creator_chain_id() {
             panic!("Messages must only be run on creation chain");
         }
 
@@ -135,6 +311,14 @@ impl Contract for MemeContract {
                 .on_msg_liquidity_funded()
                 .await
                 .expect("Failed MSG: liquidity funded"),
+            MemeMessage::OperationSucceeded { owner } => self
+                .on_msg_operation_succeeded(owner)
+                .await
+                .expect("Failed MSG: operation succeeded"),
+            MemeMessage::OperationFailed { owner, reason } => self
+                .on_msg_operation_failed(owner, reason)
+                .await
+                .expect("Failed MSG: operation failed"),
             MemeMessage::Transfer { from, to, amount } => self
                 .on_msg_transfer(from, to, amount)
                 .await
@@ -152,6 +336,10 @@ impl Contract for MemeContract {
                 .on_msg_transfer_from_application(caller, to, amount)
                 .await
                 .expect("Failed OP: trasnfer from application"),
+            MemeMessage::BatchTransfer { from, transfers } => self
+                .on_msg_batch_transfer(from, transfers)
+                .await
+                .expect("Failed MSG: batch transfer"),
             MemeMessage::InitializeLiquidity { caller, to, amount } => self
                 .on_msg_initialize_liquidity(caller, to, amount)
                 .await
@@ -160,14 +348,88 @@ impl Contract for MemeContract {
                 owner,
                 spender,
                 amount,
+                expires_at,
+            } => {
+                let result = self
+                    .on_msg_approve(owner, spender, amount, expires_at)
+                    .await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::DecreaseAllowance {
+                owner,
+                spender,
+                amount,
+            } => {
+                let result = self.on_msg_decrease_allowance(owner, spender, amount).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::RevokeAllowance { owner, spender } => {
+                let result = self.on_msg_revoke_allowance(owner, spender).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::TransferOwnership { owner, new_owner } => {
+                let result = self.on_msg_transfer_ownership(owner, new_owner).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::Snapshot { owner } => {
+                let result = self.on_msg_snapshot(owner).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::UpdateMetadata { owner, metadata } => {
+                let result = self.on_msg_update_metadata(owner, metadata).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::SetMinerBeneficiary {
+                caller,
+                miner,
+                beneficiary,
             } => self
-                .on_msg_approve(owner, spender, amount)
+                .on_msg_set_miner_beneficiary(caller, miner, beneficiary)
                 .await
-                .expect("Failed MSG: approve"),
-            MemeMessage::TransferOwnership { owner, new_owner } => self
-                .on_msg_transfer_ownership(owner, new_owner)
-                .await
-                .expect("Failed MSG: transfer ownership"),
+                .expect("Failed MSG: set miner beneficiary"),
+            MemeMessage::Stake { owner, amount } => {
+                let result = self.on_msg_stake(owner, amount).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::Unstake { owner, amount } => {
+                let result = self.on_msg_unstake(owner, amount).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::ClaimUnstaked { owner } => {
+                let result = self.on_msg_claim_unstaked(owner).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::Burn { owner, amount } => {
+                let result = self.on_msg_burn(owner, amount).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::BurnFrom {
+                owner,
+                from,
+                amount,
+            } => {
+                let result = self.on_msg_burn_from(owner, from, amount).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::ClaimVested { owner } => {
+                let result = self.on_msg_claim_vested(owner).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::PresaleBuy { owner, amount } => {
+                let result = self.on_msg_presale_buy(owner, amount).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::ClaimPresale { owner } => {
+                let result = self.on_msg_claim_presale(owner).await;
+                self.reply_operation(owner, result);
+            }
+            MemeMessage::RefundPresaleBuy { owner, amount } => {
+                self.on_msg_refund_presale_buy(owner, amount)
+            }
+            MemeMessage::Permit { caller, permit } => {
+                let result = self.on_msg_permit(permit).await;
+                self.reply_operation(caller, result);
+            }
         }
     }
 
@@ -233,32 +495,47 @@ impl MemeContract {
         }
     }
 
+    fn ams_metadata(&mut self) -> Metadata {
+        Metadata {
+            creator: self.creator(),
+            application_name: self.state.name(),
+            application_id: self.runtime.application_id().forget_abi(),
+            application_type: MEME.to_string(),
+            key_words: vec![
+                "Linera".to_string(),
+                "Meme".to_string(),
+                "PoW microchain".to_string(),
+            ],
+            logo_store_type: self.state.logo_store_type(),
+            logo: self.state.logo(),
+            description: self.state.description(),
+            twitter: self.state.twitter(),
+            telegram: self.state.telegram(),
+            discord: self.state.discord(),
+            website: self.state.website(),
+            github: self.state.github(),
+            spec: Some(serde_json::to_string(&self.state.meme()).expect("Failed serialize meme")),
+            created_at: self.runtime.system_time(),
+        }
+    }
+
     async fn register_application(&mut self) {
         if let Some(ams_application_id) = self.state.ams_application_id() {
             let call = AmsOperation::Register {
-                metadata: Metadata {
-                    creator: self.creator(),
-                    application_name: self.state.name(),
-                    application_id: self.runtime.application_id().forget_abi(),
-                    application_type: MEME.to_string(),
-                    key_words: vec![
-                        "Linera".to_string(),
-                        "Meme".to_string(),
-                        "PoW microchain".to_string(),
-                    ],
-                    logo_store_type: self.state.logo_store_type(),
-                    logo: self.state.logo(),
-                    description: self.state.description(),
-                    twitter: self.state.twitter(),
-                    telegram: self.state.telegram(),
-                    discord: self.state.discord(),
-                    website: self.state.website(),
-                    github: self.state.github(),
-                    spec: Some(
-                        serde_json::to_string(&self.state.meme()).expect("Failed serialize meme"),
-                    ),
-                    created_at: self.runtime.system_time(),
-                },
+                metadata: self.ams_metadata(),
+            };
+            let _ =
+                self.runtime
+                    .call_application(true, ams_application_id.with_abi::<AmsAbi>(), &call);
+        }
+    }
+
+    // AMS keeps creator and creation time of the listing, other fields are replaced
+    async fn update_application(&mut self) {
+        if let Some(ams_application_id) = self.state.ams_application_id() {
+            let call = AmsOperation::Update {
+                application_id: self.runtime.application_id().forget_abi(),
+                metadata: self.ams_metadata(),
             };
             let _ =
                 self.runtime
@@ -319,6 +596,36 @@ impl MemeContract {
         }
     }
 
+    // Creator's native liquidity and pool chain fee are held by application with raised presale
+    // funds, so whoever closes the presale doesn't pay for the pool
+    fn hold_presale_liquidity(&mut self) {
+        let Some(liquidity) = self.initial_liquidity() else {
+            return;
+        };
+        let application = self.application_account();
+        let amount = open_chain_fee_budget()
+            .try_add(liquidity.native_amount)
+            .expect("Invalid liquidity");
+        self.fund_account(application, amount);
+    }
+
+    // Pool funds come from signer, or from application if they're held until presale is closed
+    fn fund_pool_account(&mut self, to: Account, amount: Amount, from_application: bool) {
+        if from_application {
+            let application = AccountOwner::from(self.runtime.application_id().forget_abi());
+            self.runtime.transfer(application, to, amount);
+        } else {
+            self.fund_account(to, amount);
+        }
+    }
+
+    fn presale_raised(&self) -> Amount {
+        self.state
+            .presale()
+            .map(|presale| presale.raised)
+            .unwrap_or(Amount::ZERO)
+    }
+
     async fn create_liquidity_pool(&mut self) -> Result<(), MemeError> {
         let Some(swap_application_id) = self.state.swap_application_id() else {
             return Ok(());
@@ -333,21 +640,25 @@ impl MemeContract {
         // Meme chain will be created by swap creator chain, so we fund signer on swap creator
         // chain then it'll fund meme chain
         let swap_creator_chain = self.swap_creator_chain_id();
-        self.fund_account(
+        let presale = self.state.presale().is_some();
+        self.fund_pool_account(
             Account {
                 chain_id: swap_creator_chain,
                 owner: AccountOwner::CHAIN,
             },
             open_chain_fee_budget(),
+            presale,
         );
         if !self.virtual_initial_liquidity() {
-            // At this moment (instantiating) there is no balance on application, so we should transfer from chain
-            self.fund_account(
+            // At instantiating there is no balance on application, so we should transfer from
+            // signer. Raised presale funds are added to the held native liquidity
+            self.fund_pool_account(
                 Account {
                     chain_id: swap_creator_chain,
                     owner: AccountOwner::from(swap_application_id),
                 },
-                liquidity.native_amount,
+                liquidity.native_amount.try_add(self.presale_raised())?,
+                presale,
             );
         }
 
@@ -362,40 +673,165 @@ impl MemeContract {
         Ok(())
     }
 
+    fn emit(&mut self, event: MemeEvent) {
+        self.runtime
+            .emit(StreamName::from(MEME_STREAM_NAME), &event);
+        #[cfg(test)]
+        self.events.push(event);
+    }
+
+    // Transfer carries amount actually received after tax, so collected tax is emitted before it
+    fn emit_transfer(
+        &mut self,
+        from: Account,
+        to: Account,
+        received: Amount,
+        tax: Option<(TaxDestination, Amount)>,
+    ) {
+        if let Some((destination, amount)) = tax {
+            self.emit(MemeEvent::Tax {
+                from,
+                to,
+                amount,
+                destination,
+            });
+        }
+        self.emit(MemeEvent::Transfer {
+            from,
+            to,
+            amount: received,
+        });
+    }
+
+    async fn reclaim_expired_allowances(
+        &mut self,
+        owner: Account,
+        timestamp: Timestamp,
+    ) -> Result<(), MemeError> {
+        let amount = self
+            .state
+            .reclaim_expired_allowances(owner, timestamp)
+            .await?;
+        if amount > Amount::ZERO {
+            self.emit(MemeEvent::ReclaimAllowance { owner, amount });
+        }
+        Ok(())
+    }
+
     fn operation_executable(&mut self, operation: &MemeOperation) -> bool {
         match operation {
-            MemeOperation::Mine { .. } => {
+            MemeOperation::Mine { .. }
+            | MemeOperation::SubmitShare { .. }
+            | MemeOperation::ClosePresale => {
                 self.runtime.chain_id() == self.runtime.application_creator_chain_id()
             }
             _ => true,
         }
     }
 
-    fn on_op_transfer(&mut self, to: Account, amount: Amount) -> Result<MemeResponse, MemeError> {
-        let from = self.owner_account();
+    async fn elect_proposer(&mut self, mining: bool, sharing: bool) -> Result<(), MemeError> {
+        let ownership = self.runtime.chain_ownership();
+        let height = self.runtime.block_height();
+        let timestamp = self.runtime.system_time();
+        let signer = self.runtime.authenticated_signer();
+
+        self.state
+            .elect_proposer(
+                ownership.owners,
+                height,
+                timestamp,
+                &ownership.timeout_config,
+                signer,
+                mining,
+                sharing,
+            )
+            .await
+    }
+
+    // Messages sent by user operations are tracked in sender chain log until creation chain
+    // replies or bounces them
+    async fn send_tracked_message(
+        &mut self,
+        owner: Account,
+        kind: PendingOperationKind,
+        from: Account,
+        to: Option<Account>,
+        amount: Amount,
+        message: MemeMessage,
+    ) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.state
+            .add_pending_operation(owner, kind, from, to, amount, timestamp)
+            .await?;
+
         self.runtime
-            .prepare_message(MemeMessage::Transfer { from, to, amount })
+            .prepare_message(message)
             .with_authentication()
+            .with_tracking()
             .send_to(self.runtime.application_creator_chain_id());
+        Ok(())
+    }
+
+    async fn on_op_transfer(
+        &mut self,
+        to: Account,
+        amount: Amount,
+    ) -> Result<MemeResponse, MemeError> {
+        let from = self.owner_account();
+        self.send_tracked_message(
+            from,
+            PendingOperationKind::Transfer,
+            from,
+            Some(to),
+            amount,
+            MemeMessage::Transfer { from, to, amount },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_batch_transfer(
+        &mut self,
+        transfers: Vec<BatchTransferItem>,
+    ) -> Result<MemeResponse, MemeError> {
+        let from = self.owner_account();
+        let mut amount = Amount::ZERO;
+        for transfer in transfers.iter() {
+            amount = amount.try_add(transfer.amount)?;
+        }
+        self.send_tracked_message(
+            from,
+            PendingOperationKind::BatchTransfer,
+            from,
+            None,
+            amount,
+            MemeMessage::BatchTransfer { from, transfers },
+        )
+        .await?;
         Ok(MemeResponse::Ok)
     }
 
-    fn on_op_transfer_from(
+    async fn on_op_transfer_from(
         &mut self,
         from: Account,
         to: Account,
         amount: Amount,
     ) -> Result<MemeResponse, MemeError> {
         let owner = self.owner_account();
-        self.runtime
-            .prepare_message(MemeMessage::TransferFrom {
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::TransferFrom,
+            from,
+            Some(to),
+            amount,
+            MemeMessage::TransferFrom {
                 owner,
                 from,
                 to,
                 amount,
-            })
-            .with_authentication()
-            .send_to(self.runtime.application_creator_chain_id());
+            },
+        )
+        .await?;
         Ok(MemeResponse::Ok)
     }
 
@@ -443,32 +879,120 @@ impl MemeContract {
         Ok(MemeResponse::Ok)
     }
 
-    fn on_op_approve(
+    async fn on_op_approve(
         &mut self,
         spender: Account,
         amount: Amount,
+        expires_at: Option<Timestamp>,
     ) -> Result<MemeResponse, MemeError> {
         let owner = self.owner_account();
         if owner == spender {
             return Err(MemeError::InvalidOwner);
         }
-        self.runtime
-            .prepare_message(MemeMessage::Approve {
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::Approve,
+            owner,
+            Some(spender),
+            amount,
+            MemeMessage::Approve {
                 owner,
                 spender,
                 amount,
-            })
-            .with_authentication()
-            .send_to(self.runtime.application_creator_chain_id());
+                expires_at,
+            },
+        )
+        .await?;
         Ok(MemeResponse::Ok)
     }
 
-    fn on_op_transfer_ownership(&mut self, new_owner: Account) -> Result<MemeResponse, MemeError> {
+    async fn on_op_decrease_allowance(
+        &mut self,
+        spender: Account,
+        amount: Amount,
+    ) -> Result<MemeResponse, MemeError> {
         let owner = self.owner_account();
-        self.runtime
-            .prepare_message(MemeMessage::TransferOwnership { owner, new_owner })
-            .with_authentication()
-            .send_to(self.runtime.application_creator_chain_id());
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::DecreaseAllowance,
+            owner,
+            Some(spender),
+            amount,
+            MemeMessage::DecreaseAllowance {
+                owner,
+                spender,
+                amount,
+            },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_revoke_allowance(
+        &mut self,
+        spender: Account,
+    ) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::RevokeAllowance,
+            owner,
+            Some(spender),
+            Amount::ZERO,
+            MemeMessage::RevokeAllowance { owner, spender },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_transfer_ownership(
+        &mut self,
+        new_owner: Account,
+    ) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::TransferOwnership,
+            owner,
+            Some(new_owner),
+            Amount::ZERO,
+            MemeMessage::TransferOwnership { owner, new_owner },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_snapshot(&mut self) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::Snapshot,
+            owner,
+            None,
+            Amount::ZERO,
+            MemeMessage::Snapshot { owner },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_update_metadata(
+        &mut self,
+        metadata: MemeMetadata,
+    ) -> Result<MemeResponse, MemeError> {
+        if !metadata.validate() {
+            return Err(MemeError::InvalidMetadata);
+        }
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::UpdateMetadata,
+            owner,
+            None,
+            Amount::ZERO,
+            MemeMessage::UpdateMetadata { owner, metadata },
+        )
+        .await?;
         Ok(MemeResponse::Ok)
     }
 
@@ -486,40 +1010,315 @@ impl MemeContract {
         let caller = self.message_caller_account();
         let from = self.message_owner_account();
         match self.state.transfer_ensure(from, caller, amount).await {
-            Ok(_) => Ok(MemeResponse::Ok),
+            Ok((received, tax)) => {
+                self.emit_transfer(from, caller, received, tax);
+                // Caller may be the pool which needs to know the taxed amount it really received
+                Ok(MemeResponse::Amount(received))
+            }
             Err(err) => Ok(MemeResponse::Fail(err.to_string())),
         }
     }
 
-    // TODO: check first operation of the block must be mine
-    // TODO: distribute reward to block proposer
-    fn on_op_mine(&mut self, _nonce: CryptoHash) -> Result<MemeResponse, MemeError> {
+    // With proof of work election, other operations of the block are rejected by elect_proposer
+    // until it's mined, so mine is always the first meme operation of a proposed block
+    async fn on_op_mine(&mut self, nonce: CryptoHash) -> Result<MemeResponse, MemeError> {
+        let chain_id = self.runtime.chain_id();
+        let height = self.runtime.block_height();
+        let miner = self.owner_account();
+        let timestamp = self.runtime.system_time();
+
+        // Reward is decided by mined blocks before this one
+        let reward = self.state.current_block_reward();
+        let shares = self.state.reward_shares(reward);
+        let liquidity = shares.liquidity;
+
+        self.state
+            .mine(chain_id, height, miner, nonce, timestamp)
+            .await?;
+
+        // Proposer reward is settled to shares of mining pool. Nonce is bound to signer, but
+        // reward goes to its beneficiary if it's set
+        for (member, amount) in self.state.settle_shares(miner, shares.proposer).await? {
+            let to = self
+                .state
+                .miner_beneficiary(member)
+                .await?
+                .unwrap_or(member);
+            self.state.mint(to, amount).await?;
+            if amount > Amount::ZERO {
+                self.emit(MemeEvent::Mint { to, amount });
+            }
+        }
+        let creator = self.creator();
+        for (to, amount) in self.state.mint_reward(creator, shares).await? {
+            self.emit(MemeEvent::Mint { to, amount });
+        }
+        self.top_up_liquidity(liquidity).await?;
+
+        self.emit(MemeEvent::Mine {
+            miner,
+            height,
+            reward,
+        });
+
         Ok(MemeResponse::Ok)
     }
 
-    async fn on_msg_liquidity_funded(&mut self) -> Result<(), MemeError> {
-        let virtual_liquidity = self.virtual_initial_liquidity();
-        let Some(liquidity) = self.initial_liquidity() else {
-            return Ok(());
-        };
-        let Some(swap_application_id) = self.state.swap_application_id() else {
-            return Ok(());
-        };
+    async fn on_op_submit_share(&mut self, nonce: CryptoHash) -> Result<MemeResponse, MemeError> {
+        let chain_id = self.runtime.chain_id();
+        let height = self.runtime.block_height();
+        let miner = self.owner_account();
 
-        let creator = self.creator();
-        let call = SwapOperation::InitializeLiquidity {
-            creator,
-            token_0_creator_chain_id: self.runtime.chain_id(),
-            token_0: self.runtime.application_id().forget_abi(),
-            amount_0: liquidity.fungible_amount,
-            amount_1: liquidity.native_amount,
-            virtual_liquidity,
-            to: None,
-        };
-        let _ =
-            self.runtime
-                .call_application(true, swap_application_id.with_abi::<SwapAbi>(), &call);
-        Ok(())
+        self.state
+            .submit_share(chain_id, height, miner, nonce)
+            .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    fn on_op_set_miner_beneficiary(
+        &mut self,
+        miner: Account,
+        beneficiary: Option<Account>,
+    ) -> Result<MemeResponse, MemeError> {
+        let caller_id = self.runtime.authenticated_caller_id().unwrap();
+        let caller = Account {
+            chain_id: self.runtime.chain_id(),
+            owner: AccountOwner::from(caller_id),
+        };
+
+        self.runtime
+            .prepare_message(MemeMessage::SetMinerBeneficiary {
+                caller,
+                miner,
+                beneficiary,
+            })
+            .with_authentication()
+            .send_to(self.runtime.application_creator_chain_id());
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_stake(&mut self, amount: Amount) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::Stake,
+            owner,
+            None,
+            amount,
+            MemeMessage::Stake { owner, amount },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_unstake(&mut self, amount: Amount) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::Unstake,
+            owner,
+            None,
+            amount,
+            MemeMessage::Unstake { owner, amount },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_claim_unstaked(&mut self) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::ClaimUnstaked,
+            owner,
+            None,
+            Amount::ZERO,
+            MemeMessage::ClaimUnstaked { owner },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_claim_vested(&mut self) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::ClaimVested,
+            owner,
+            None,
+            Amount::ZERO,
+            MemeMessage::ClaimVested { owner },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_presale_buy(&mut self, amount: Amount) -> Result<MemeResponse, MemeError> {
+        // Paid native tokens are held by application until pool is created
+        let application = self.application_creation_account();
+        self.fund_account(application, amount);
+
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::PresaleBuy,
+            owner,
+            None,
+            amount,
+            MemeMessage::PresaleBuy { owner, amount },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_close_presale(&mut self) -> Result<MemeResponse, MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.state.close_presale(timestamp).await?;
+        self.create_liquidity_pool().await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_claim_presale(&mut self) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::ClaimPresale,
+            owner,
+            None,
+            Amount::ZERO,
+            MemeMessage::ClaimPresale { owner },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    // Permit could be submitted by anyone, approval is authorized by owner signature only
+    async fn on_op_permit(
+        &mut self,
+        permit: Permit,
+        public_key: AccountPublicKey,
+        signature: AccountSignature,
+    ) -> Result<MemeResponse, MemeError> {
+        if permit.token != self.runtime.application_id().forget_abi() {
+            return Err(MemeError::InvalidPermitToken);
+        }
+        if !permit.verify(public_key, &signature) {
+            return Err(MemeError::InvalidSignature);
+        }
+
+        // Permit is tracked in log of whoever submits it
+        let caller = self.owner_account();
+        self.send_tracked_message(
+            caller,
+            PendingOperationKind::Permit,
+            permit.owner,
+            Some(permit.spender),
+            permit.amount,
+            MemeMessage::Permit { caller, permit },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_burn(&mut self, amount: Amount) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::Burn,
+            owner,
+            None,
+            amount,
+            MemeMessage::Burn { owner, amount },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn on_op_burn_from(
+        &mut self,
+        from: Account,
+        amount: Amount,
+    ) -> Result<MemeResponse, MemeError> {
+        let owner = self.owner_account();
+        self.send_tracked_message(
+            owner,
+            PendingOperationKind::BurnFrom,
+            from,
+            None,
+            amount,
+            MemeMessage::BurnFrom {
+                owner,
+                from,
+                amount,
+            },
+        )
+        .await?;
+        Ok(MemeResponse::Ok)
+    }
+
+    async fn top_up_liquidity(&mut self, amount: Amount) -> Result<(), MemeError> {
+        if amount == Amount::ZERO {
+            return Ok(());
+        }
+
+        let swap_creator_chain = self.swap_creator_chain_id();
+        self.state
+            .approve_liquidity(amount, swap_creator_chain)
+            .await?;
+
+        let call = SwapOperation::TopUpLiquidity {
+            token_0_creator_chain_id: self.runtime.chain_id(),
+            token_0: self.runtime.application_id().forget_abi(),
+            amount_0: amount,
+        };
+        let swap_application_id = self.state.swap_application_id().unwrap();
+        let _ =
+            self.runtime
+                .call_application(true, swap_application_id.with_abi::<SwapAbi>(), &call);
+        Ok(())
+    }
+
+    async fn on_msg_liquidity_funded(&mut self) -> Result<(), MemeError> {
+        let virtual_liquidity = self.virtual_initial_liquidity();
+        let Some(liquidity) = self.initial_liquidity() else {
+            return Ok(());
+        };
+        let Some(swap_application_id) = self.state.swap_application_id() else {
+            return Ok(());
+        };
+
+        let creator = self.creator();
+        let call = SwapOperation::InitializeLiquidity {
+            creator,
+            token_0_creator_chain_id: self.runtime.chain_id(),
+            token_0: self.runtime.application_id().forget_abi(),
+            amount_0: liquidity.fungible_amount,
+            amount_1: liquidity.native_amount.try_add(self.presale_raised())?,
+            virtual_liquidity,
+            to: None,
+            buy_bps: self
+                .state
+                .transfer_tax()
+                .map_or(0, |transfer_tax| transfer_tax.buy_bps),
+        };
+        let _ =
+            self.runtime
+                .call_application(true, swap_application_id.with_abi::<SwapAbi>(), &call);
+        Ok(())
+    }
+
+    // Failed tracked message doesn't reject the message, reason is replied to sender chain instead
+    fn reply_operation(&mut self, owner: Account, result: Result<(), MemeError>) {
+        let message = match result {
+            Ok(_) => MemeMessage::OperationSucceeded { owner },
+            Err(err) => MemeMessage::OperationFailed {
+                owner,
+                reason: err.to_string(),
+            },
+        };
+        self.runtime
+            .prepare_message(message)
+            .send_to(owner.chain_id);
     }
 
     async fn on_msg_transfer(
@@ -528,7 +1327,46 @@ impl MemeContract {
         to: Account,
         amount: Amount,
     ) -> Result<(), MemeError> {
-        self.state.transfer(from, to, amount).await
+        let result = self.try_transfer(from, to, amount).await;
+        self.reply_operation(from, result);
+        Ok(())
+    }
+
+    async fn try_transfer(
+        &mut self,
+        from: Account,
+        to: Account,
+        amount: Amount,
+    ) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.reclaim_expired_allowances(from, timestamp).await?;
+        let (received, tax) = self.state.transfer_ensure(from, to, amount).await?;
+        self.emit_transfer(from, to, received, tax);
+        Ok(())
+    }
+
+    async fn on_msg_batch_transfer(
+        &mut self,
+        from: Account,
+        transfers: Vec<BatchTransferItem>,
+    ) -> Result<(), MemeError> {
+        let result = self.try_batch_transfer(from, transfers).await;
+        self.reply_operation(from, result);
+        Ok(())
+    }
+
+    async fn try_batch_transfer(
+        &mut self,
+        from: Account,
+        transfers: Vec<BatchTransferItem>,
+    ) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.reclaim_expired_allowances(from, timestamp).await?;
+        let receipts = self.state.batch_transfer(from, transfers.clone()).await?;
+        for (transfer, (received, tax)) in transfers.into_iter().zip(receipts) {
+            self.emit_transfer(from, transfer.to, received, tax);
+        }
+        Ok(())
     }
 
     async fn on_msg_transfer_from(
@@ -538,7 +1376,79 @@ impl MemeContract {
         to: Account,
         amount: Amount,
     ) -> Result<(), MemeError> {
-        self.state.transfer_from(owner, from, to, amount).await
+        let result = self.try_transfer_from(owner, from, to, amount).await;
+        self.reply_operation(owner, result);
+        Ok(())
+    }
+
+    async fn try_transfer_from(
+        &mut self,
+        owner: Account,
+        from: Account,
+        to: Account,
+        amount: Amount,
+    ) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        let (received, tax) = self
+            .state
+            .transfer_from(owner, from, to, amount, timestamp)
+            .await?;
+        self.emit_transfer(from, to, received, tax);
+        Ok(())
+    }
+
+    async fn on_msg_operation_succeeded(&mut self, owner: Account) -> Result<(), MemeError> {
+        self.state
+            .resolve_pending_operation(owner, PendingOperationStatus::Succeeded, None)
+            .await
+    }
+
+    async fn on_msg_operation_failed(
+        &mut self,
+        owner: Account,
+        reason: String,
+    ) -> Result<(), MemeError> {
+        self.state
+            .resolve_pending_operation(owner, PendingOperationStatus::Failed, Some(reason))
+            .await
+    }
+
+    // Only messages sent by user operations are tracked, messages sent by other applications
+    // are not bounced
+    async fn on_msg_bounced(&mut self, message: MemeMessage) -> Result<(), MemeError> {
+        let owner = match message {
+            MemeMessage::Transfer { from, .. } | MemeMessage::BatchTransfer { from, .. } => from,
+            MemeMessage::Permit { caller, .. } => caller,
+            MemeMessage::TransferFrom { owner, .. }
+            | MemeMessage::Approve { owner, .. }
+            | MemeMessage::DecreaseAllowance { owner, .. }
+            | MemeMessage::RevokeAllowance { owner, .. }
+            | MemeMessage::TransferOwnership { owner, .. }
+            | MemeMessage::Snapshot { owner }
+            | MemeMessage::UpdateMetadata { owner, .. }
+            | MemeMessage::Stake { owner, .. }
+            | MemeMessage::Unstake { owner, .. }
+            | MemeMessage::ClaimUnstaked { owner }
+            | MemeMessage::ClaimVested { owner }
+            | MemeMessage::ClaimPresale { owner }
+            | MemeMessage::Burn { owner, .. }
+            | MemeMessage::BurnFrom { owner, .. } => owner,
+            MemeMessage::PresaleBuy { owner, amount } => {
+                // Paid funds were transferred to creation chain with the rejected message
+                self.runtime
+                    .prepare_message(MemeMessage::RefundPresaleBuy { owner, amount })
+                    .send_to(self.runtime.application_creator_chain_id());
+                owner
+            }
+            _ => return Ok(()),
+        };
+        self.state
+            .resolve_pending_operation(
+                owner,
+                PendingOperationStatus::Failed,
+                Some("Rejected by creation chain".to_string()),
+            )
+            .await
     }
 
     async fn on_msg_transfer_from_application(
@@ -547,7 +1457,9 @@ impl MemeContract {
         to: Account,
         amount: Amount,
     ) -> Result<(), MemeError> {
-        self.state.transfer(caller, to, amount).await
+        let (received, tax) = self.state.transfer(caller, to, amount).await?;
+        self.emit_transfer(caller, to, received, tax);
+        Ok(())
     }
 
     async fn on_msg_initialize_liquidity(
@@ -565,8 +1477,54 @@ impl MemeContract {
             "Invalid caller"
         );
 
+        // Initial liquidity is already in pool reserves when it's created, later liquidity is
+        // topped up to the same pool
+        let top_up = self.state.pool_application() == Some(to.owner);
+        // Meme native pool is the only one whose transfers are taxed as buy and sell
+        self.state.set_pool_application(to.owner);
+
         let from = self.application_creation_account();
-        self.state.transfer_from(caller, from, to, amount).await
+        let timestamp = self.runtime.system_time();
+        let (received, tax) = self
+            .state
+            .transfer_from(caller, from, to, amount, timestamp)
+            .await?;
+        self.emit_transfer(from, to, received, tax);
+
+        if top_up {
+            self.top_up_pool_reserve(to, received);
+        }
+        Ok(())
+    }
+
+    // Pool reserve is only credited after the transfer is done, so a rejected transfer won't
+    // leave the pool with reserve it doesn't hold
+    fn top_up_pool_reserve(&mut self, pool_application: Account, amount: Amount) {
+        let AccountOwner::Address32(application_description_hash) = pool_application.owner else {
+            panic!("Invalid owner");
+        };
+        let application_id = ApplicationId::new(application_description_hash);
+        let call = PoolOperation::TopUpReserve { amount_0: amount };
+        let _ = self
+            .runtime
+            .call_application(true, application_id.with_abi::<PoolAbi>(), &call);
+    }
+
+    async fn on_msg_set_miner_beneficiary(
+        &mut self,
+        caller: Account,
+        miner: Account,
+        beneficiary: Option<Account>,
+    ) -> Result<(), MemeError> {
+        let Some(proxy_application_id) = self.state.proxy_application_id() else {
+            panic!("Invalid caller");
+        };
+        assert!(
+            caller.owner == AccountOwner::from(proxy_application_id),
+            "Invalid caller"
+        );
+
+        self.state.set_miner_beneficiary(miner, beneficiary).await
     }
 
     async fn on_msg_approve(
@@ -574,390 +1532,3196 @@ impl MemeContract {
         owner: Account,
         spender: Account,
         amount: Amount,
+        expires_at: Option<Timestamp>,
     ) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        // Allowance which is already expired would only lock funds until it's reclaimed
+        if expires_at.is_some_and(|expires_at| expires_at <= timestamp) {
+            return Err(MemeError::InvalidExpiry);
+        }
+        self.reclaim_expired_allowances(owner, timestamp).await?;
+
         let balance = self.state.balance_of(owner).await;
-        assert!(amount <= balance, "Insufficient balance");
+        if amount > balance {
+            return Err(MemeError::InsufficientFunds);
+        }
 
-        self.state.approve(owner, spender, amount).await
+        self.state
+            .approve(owner, spender, amount, expires_at)
+            .await?;
+        self.emit(MemeEvent::Approve {
+            owner,
+            spender,
+            amount,
+        });
+        Ok(())
     }
 
-    async fn on_msg_transfer_ownership(
+    async fn on_msg_decrease_allowance(
         &mut self,
         owner: Account,
-        new_owner: Account,
+        spender: Account,
+        amount: Amount,
     ) -> Result<(), MemeError> {
-        self.state.transfer_ownership(owner, new_owner).await
+        self.state
+            .decrease_allowance(owner, spender, amount)
+            .await?;
+        self.emit(MemeEvent::DecreaseAllowance {
+            owner,
+            spender,
+            amount,
+        });
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use abi::{
-        meme::{
-            InstantiationArgument, Liquidity, Meme, MemeAbi, MemeMessage, MemeOperation,
-            MemeParameters, MemeResponse, Metadata,
-        },
-        store_type::StoreType,
-        swap::router::SwapResponse,
-    };
-    use futures::FutureExt as _;
-    use linera_sdk::{
-        bcs,
-        linera_base_types::{
-            Account, AccountOwner, Amount, ApplicationId, ChainId, ChainOwnership, CryptoHash,
-            TestString,
-        },
-        util::BlockingWait,
-        views::View,
-        Contract, ContractRuntime,
-    };
-    use std::str::FromStr;
+    async fn on_msg_revoke_allowance(
+        &mut self,
+        owner: Account,
+        spender: Account,
+    ) -> Result<(), MemeError> {
+        let amount = self.state.revoke_allowance(owner, spender).await?;
+        self.emit(MemeEvent::RevokeAllowance {
+            owner,
+            spender,
+            amount,
+        });
+        Ok(())
+    }
 
-    use super::{MemeContract, MemeState};
+    async fn on_msg_stake(&mut self, owner: Account, amount: Amount) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.reclaim_expired_allowances(owner, timestamp).await?;
+        self.state.stake(owner, amount).await?;
+        self.emit(MemeEvent::Stake { owner, amount });
+        Ok(())
+    }
 
-    #[tokio::test(flavor = "multi_thread")]
-    async fn creation_chain_operation() {
-        let mut meme = create_and_instantiate_meme().await;
+    async fn on_msg_unstake(&mut self, owner: Account, amount: Amount) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.state.unstake(owner, amount, timestamp).await?;
+        self.emit(MemeEvent::Unstake { owner, amount });
+        Ok(())
+    }
 
-        let response = meme
-            .execute_operation(MemeOperation::Mine {
-                nonce: CryptoHash::new(&TestString::new("aaaa")),
-            })
-            .now_or_never()
-            .expect("Execution of meme operation should not await anything");
+    async fn on_msg_claim_unstaked(&mut self, owner: Account) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        let amount = self.state.claim_unstaked(owner, timestamp).await?;
+        self.emit(MemeEvent::ClaimUnstaked { owner, amount });
+        Ok(())
+    }
 
-        assert!(matches!(response, MemeResponse::Ok));
+    async fn on_msg_claim_vested(&mut self, owner: Account) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        let amount = self.state.claim_vested(owner, timestamp).await?;
+        self.emit(MemeEvent::ClaimVested { owner, amount });
+        Ok(())
     }
 
-    #[tokio::test(flavor = "multi_thread")]
-    async fn user_chain_operation() {
-        let mut meme = create_and_instantiate_meme().await;
-        let to = Account {
-            chain_id: meme.runtime.chain_id(),
+    // Paid native tokens are credited to application before this message, so they're refunded to
+    // buyer if the buy is rejected
+    async fn on_msg_presale_buy(
+        &mut self,
+        owner: Account,
+        amount: Amount,
+    ) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        let result = self
+            .state
+            .presale_buy(owner, amount, timestamp)
+            .await
+            .map(|_| ());
+        if result.is_err() && amount > Amount::ZERO {
+            let application = AccountOwner::from(self.runtime.application_id().forget_abi());
+            self.runtime.transfer(application, owner, amount);
+        }
+        result
+    }
+
+    fn on_msg_refund_presale_buy(&mut self, owner: Account, amount: Amount) {
+        let application = AccountOwner::from(self.runtime.application_id().forget_abi());
+        self.runtime.transfer(application, owner, amount);
+    }
+
+    async fn on_msg_claim_presale(&mut self, owner: Account) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        let amount = self.state.claim_presale(owner, timestamp).await?;
+        self.emit(MemeEvent::ClaimPresale { owner, amount });
+        Ok(())
+    }
+
+    async fn on_msg_permit(&mut self, permit: Permit) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.reclaim_expired_allowances(permit.owner, timestamp)
+            .await?;
+        self.state.permit(permit.clone(), timestamp).await?;
+        self.emit(MemeEvent::Approve {
+            owner: permit.owner,
+            spender: permit.spender,
+            amount: permit.amount,
+        });
+        Ok(())
+    }
+
+    async fn on_msg_burn(&mut self, owner: Account, amount: Amount) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.reclaim_expired_allowances(owner, timestamp).await?;
+        self.state.burn(owner, amount).await?;
+        self.emit(MemeEvent::Burn { owner, amount });
+        Ok(())
+    }
+
+    async fn on_msg_burn_from(
+        &mut self,
+        owner: Account,
+        from: Account,
+        amount: Amount,
+    ) -> Result<(), MemeError> {
+        let timestamp = self.runtime.system_time();
+        self.state.burn_from(owner, from, amount, timestamp).await?;
+        self.emit(MemeEvent::Burn {
+            owner: from,
+            amount,
+        });
+        Ok(())
+    }
+
+    async fn on_msg_transfer_ownership(
+        &mut self,
+        owner: Account,
+        new_owner: Account,
+    ) -> Result<(), MemeError> {
+        self.state.transfer_ownership(owner, new_owner).await?;
+        self.emit(MemeEvent::OwnershipTransferred {
+            previous_owner: owner,
+            new_owner,
+        });
+        Ok(())
+    }
+
+    async fn on_msg_update_metadata(
+        &mut self,
+        owner: Account,
+        metadata: MemeMetadata,
+    ) -> Result<(), MemeError> {
+        if owner != self.state.owner().await {
+            return Err(MemeError::InvalidOwner);
+        }
+        if !metadata.validate() {
+            return Err(MemeError::InvalidMetadata);
+        }
+
+        let logo_changed = metadata.logo != Some(self.state.logo())
+            || metadata.logo_store_type != self.state.logo_store_type();
+        self.state.update_metadata(metadata);
+
+        if logo_changed {
+            self.register_logo().await;
+        }
+        self.update_application().await;
+        Ok(())
+    }
+
+    async fn on_msg_snapshot(&mut self, owner: Account) -> Result<(), MemeError> {
+        if owner != self.state.owner().await {
+            return Err(MemeError::InvalidOwner);
+        }
+        let timestamp = self.runtime.system_time();
+        let id = self.state.snapshot(timestamp)?;
+        self.emit(MemeEvent::Snapshot { id });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abi::{
+        ams::{AmsOperation, AmsResponse},
+        blob_gateway::{BlobDataType, BlobGatewayOperation},
+        election::{round_robin_leader, LeaderElectionMethod},
+        meme::{
+            AirdropSplit, Allowance, BatchTransferItem, EmissionPolicy, GenesisMinerAirdrop,
+            HashAlgorithm, Holder, HolderCursor, InstantiationArgument, Liquidity, Meme, MemeAbi,
+            MemeEvent, MemeMessage, MemeOperation, MemeParameters, MemeResponse, Metadata,
+            MiningConfig, MiningPool, PendingOperationKind, PendingOperationStatus, Permit,
+            PresaleConfig, RewardDecay, RewardSplit, StakingConfig, TaxDestination, TeamAllocation,
+            TransferTax, VestingRelease, VestingSchedule, WhitelistAllocation,
+        },
+        pow::{MiningChallenge, MAX_DIFFICULTY_ADJUSTMENT},
+        store_type::StoreType,
+        swap::router::SwapResponse,
+    };
+    use futures::FutureExt as _;
+    use linera_sdk::{
+        bcs,
+        linera_base_types::{
+            Account, AccountOwner, AccountSecretKey, AccountSignature, Amount, ApplicationId,
+            BlockHeight, ChainId, ChainOwnership, CryptoHash, Ed25519SecretKey, TestString,
+            TimeoutConfig, Timestamp,
+        },
+        util::BlockingWait,
+        views::View,
+        Contract, ContractRuntime,
+    };
+    use meme::MemeError;
+    use std::{
+        collections::BTreeMap,
+        str::FromStr,
+        sync::{Arc, Mutex},
+    };
+
+    use super::{MemeContract, MemeState};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn creation_chain_operation() {
+        let mut meme = create_and_instantiate_meme().await;
+
+        let response = meme
+            .execute_operation(MemeOperation::Mine {
+                nonce: CryptoHash::new(&TestString::new("aaaa")),
+            })
+            .now_or_never()
+            .expect("Execution of meme operation should not await anything");
+
+        assert!(matches!(response, MemeResponse::Ok));
+
+        let mining_info = meme.state.mining_info();
+        assert_eq!(mining_info.mined_height, Some(BlockHeight::from(1)));
+        assert_eq!(mining_info.mined_blocks, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mine_mint_block_reward() {
+        let mut meme = create_and_instantiate_meme().await;
+        let miner = meme.owner_account();
+        let balance = meme.state.balance_of(miner).await;
+        let total_supply = meme.state.meme().total_supply;
+        let reward = meme.state.current_block_reward();
+        assert_eq!(reward, Amount::from_tokens(50));
+
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        assert_eq!(
+            meme.state.balance_of(miner).await,
+            balance.try_add(reward).unwrap()
+        );
+        assert_eq!(
+            meme.state.meme().total_supply,
+            total_supply.try_add(reward).unwrap()
+        );
+        assert_eq!(meme.state.mined_supply(), reward);
+        assert_eq!(
+            meme.state.remaining_supply(),
+            Amount::from_tokens(21000000).try_sub(reward).unwrap()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mine_split_block_reward() {
+        let chain_id = meme_chain_id();
+        let treasury = other_user(chain_id);
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            reward_split: Some(RewardSplit {
+                proposer_bps: 6000,
+                creator_bps: 1000,
+                treasury_bps: 2000,
+                treasury: Some(treasury),
+                liquidity_bps: 1000,
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        // Miner is also the creator in test
+        let miner = meme.owner_account();
+        let application = meme.application_account();
+        let swap_application = Account {
+            chain_id,
+            owner: AccountOwner::from(meme.state.swap_application_id().unwrap()),
+        };
+
+        let balance = meme.state.balance_of(miner).await;
+        let application_balance = meme.state.balance_of(application).await;
+        let allowance = meme.state.allowance_of(application, swap_application).await;
+
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        assert_eq!(
+            meme.state.balance_of(miner).await,
+            balance.try_add(Amount::from_tokens(35)).unwrap()
+        );
+        assert_eq!(
+            meme.state.balance_of(treasury).await,
+            Amount::from_tokens(10)
+        );
+        // Liquidity share is approved to swap application
+        assert_eq!(
+            meme.state.balance_of(application).await,
+            application_balance
+        );
+        assert_eq!(
+            meme.state.allowance_of(application, swap_application).await,
+            allowance.try_add(Amount::from_tokens(5)).unwrap()
+        );
+        assert_eq!(meme.state.mined_supply(), Amount::from_tokens(50));
+
+        // Each minted share is visible to indexers
+        for (to, amount) in [
+            (miner, Amount::from_tokens(30)),
+            (miner, Amount::from_tokens(5)),
+            (treasury, Amount::from_tokens(10)),
+            (application, Amount::from_tokens(5)),
+        ] {
+            assert!(meme.events.contains(&MemeEvent::Mint { to, amount }));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mine_pay_miner_beneficiary() {
+        let mut meme = create_and_instantiate_meme().await;
+        let chain_id = meme.runtime.chain_id();
+
+        let proxy_application_id = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5baf",
+        )
+        .unwrap();
+        meme.state
+            .proxy_application_id
+            .set(Some(proxy_application_id));
+
+        let miner = meme.owner_account();
+        let beneficiary = other_user(chain_id);
+
+        meme.execute_message(MemeMessage::SetMinerBeneficiary {
+            caller: Account {
+                chain_id,
+                owner: AccountOwner::from(proxy_application_id),
+            },
+            miner,
+            beneficiary: Some(beneficiary),
+        })
+        .await;
+
+        let balance = meme.state.balance_of(miner).await;
+        let reward = meme.state.current_block_reward();
+
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        assert_eq!(meme.state.balance_of(miner).await, balance);
+        assert_eq!(meme.state.balance_of(beneficiary).await, reward);
+    }
+
+    fn enable_mining_pool(meme: &mut MemeContract, pplns_window: u64) {
+        let mut meme_info = meme.state.meme();
+        meme_info.mining.mining_pool = Some(MiningPool {
+            share_difficulty_divisor: 1,
+            pplns_window,
+        });
+        meme.state.meme.set(Some(meme_info));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn settle_shares_weighted_by_difficulty() {
+        let mut meme = create_and_instantiate_meme().await;
+        enable_mining_pool(&mut meme, 4);
+
+        let chain_id = meme.runtime.chain_id();
+        let miner = meme.owner_account();
+        let member = team_member(chain_id);
+
+        // Member share is submitted before difficulty is retargeted from 3 to 1
+        for (index, (owner, difficulty)) in [(member, 3), (miner, 1)].into_iter().enumerate() {
+            meme.state
+                .shares
+                .insert(
+                    &(index as u64),
+                    MiningShare {
+                        miner: owner,
+                        hash: CryptoHash::new(&TestString::new(format!("share {}", index))),
+                        height: BlockHeight::from(1),
+                        difficulty,
+                    },
+                )
+                .unwrap();
+        }
+        meme.state.share_index.set(2);
+
+        let rewards = meme
+            .state
+            .settle_shares(miner, Amount::from_tokens(4))
+            .await
+            .unwrap();
+        assert!(rewards.contains(&(member, Amount::from_tokens(3))));
+        assert_eq!(
+            rewards
+                .iter()
+                .filter(|(account, _)| *account == miner)
+                .fold(Amount::ZERO, |total, (_, amount)| total
+                    .try_add(*amount)
+                    .unwrap()),
+            Amount::ONE
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mine_settle_pool_shares() {
+        let mut meme = create_and_instantiate_meme().await;
+        enable_mining_pool(&mut meme, 4);
+
+        let chain_id = meme.runtime.chain_id();
+        let miner = meme.owner_account();
+        let member = other_user(chain_id);
+
+        // This share will be out of PPLNS window
+        meme.execute_operation(MemeOperation::SubmitShare {
+            nonce: CryptoHash::new(&TestString::new("share 0")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        meme.runtime.set_authenticated_signer(Some(member.owner));
+        for i in 1..4 {
+            meme.execute_operation(MemeOperation::SubmitShare {
+                nonce: CryptoHash::new(&TestString::new(format!("share {}", i))),
+            })
+            .now_or_never()
+            .expect("Execution of meme operation should not await anything");
+        }
+        meme.runtime.set_authenticated_signer(Some(miner.owner));
+
+        let balance = meme.state.balance_of(miner).await;
+        let reward = meme.state.current_block_reward();
+
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        // Mined nonce is also a share, so member has 3 shares and miner has 1 in window
+        let member_reward = Amount::from_attos(u128::from(reward) * 3 / 4);
+        assert_eq!(meme.state.balance_of(member).await, member_reward);
+        assert_eq!(
+            meme.state.balance_of(miner).await,
+            balance
+                .try_add(reward.try_sub(member_reward).unwrap())
+                .unwrap()
+        );
+        assert_eq!(meme.state.pplns_shares().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed OP: submit share: DuplicateShare")]
+    async fn submit_duplicate_share() {
+        let mut meme = create_and_instantiate_meme().await;
+        enable_mining_pool(&mut meme, 4);
+
+        for _ in 0..2 {
+            meme.execute_operation(MemeOperation::SubmitShare {
+                nonce: CryptoHash::new(&TestString::new("share")),
+            })
+            .now_or_never()
+            .expect("Execution of meme operation should not await anything");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed OP: submit share: NotMiningPool")]
+    async fn submit_share_without_mining_pool() {
+        let mut meme = create_and_instantiate_meme().await;
+
+        meme.execute_operation(MemeOperation::SubmitShare {
+            nonce: CryptoHash::new(&TestString::new("share")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Invalid reward split")]
+    async fn instantiate_invalid_reward_split() {
+        create_and_instantiate_meme_with(MemeOptions {
+            reward_split: Some(RewardSplit {
+                proposer_bps: 6000,
+                creator_bps: 1000,
+                treasury_bps: 0,
+                treasury: None,
+                liquidity_bps: 1000,
+            }),
+            ..Default::default()
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed OP: mine: AlreadyMined")]
+    async fn mine_twice_at_same_height() {
+        let mut meme = create_and_instantiate_meme().await;
+
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("bbbb")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed OP: mine: InvalidNonce")]
+    async fn mine_invalid_nonce() {
+        let mut meme = create_and_instantiate_meme().await;
+
+        let mut mining_info = meme.state.mining_info();
+        mining_info.difficulty = u64::MAX;
+        meme.state.mining_info.set(Some(mining_info));
+
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mine_with_pow_algorithm() {
+        let mut meme = create_and_instantiate_meme().await;
+
+        let mut meme_info = meme.state.meme();
+        meme_info.mining.pow_algorithm = HashAlgorithm::Blake3;
+        meme.state.meme.set(Some(meme_info));
+
+        let mut mining_info = meme.state.mining_info();
+        mining_info.difficulty = 256;
+        meme.state.mining_info.set(Some(mining_info.clone()));
+
+        let challenge = MiningChallenge {
+            chain_id: meme.runtime.chain_id(),
+            height: meme.runtime.block_height(),
+            previous_hash: mining_info.previous_hash,
+            miner: meme.owner_account(),
+        };
+        // Nonce is only valid for configured algorithm
+        let nonce = (0..100000)
+            .map(|i| CryptoHash::new(&TestString::new(format!("nonce {}", i))))
+            .find(|nonce| {
+                challenge.verify(&HashAlgorithm::Blake3, *nonce, 256)
+                    && !challenge.verify(&HashAlgorithm::Keccak256, *nonce, 256)
+            })
+            .expect("Failed mine nonce");
+
+        meme.execute_operation(MemeOperation::Mine { nonce })
+            .now_or_never()
+            .expect("Execution of meme operation should not await anything");
+
+        assert_eq!(
+            meme.state.mining_info().previous_hash,
+            challenge.hash(&HashAlgorithm::Blake3, nonce)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mine_retarget_difficulty() {
+        let mut meme = create_and_instantiate_meme().await;
+        let retarget_interval = meme.state.meme().mining.retarget_interval;
+
+        for height in 1..=retarget_interval + 1 {
+            meme.runtime.set_block_height(BlockHeight::from(height));
+            meme.execute_operation(MemeOperation::Mine {
+                nonce: CryptoHash::new(&TestString::new(format!("nonce {}", height))),
+            })
+            .now_or_never()
+            .expect("Execution of meme operation should not await anything");
+        }
+
+        // All blocks are mined at the same time, so difficulty is raised as much as allowed
+        assert_eq!(meme.state.difficulty(), MAX_DIFFICULTY_ADJUSTMENT);
+        assert_eq!(meme.state.mining_info().window_blocks, 0);
+    }
+
+    fn set_meme_chain_owners(meme: &mut MemeContract) -> (AccountOwner, AccountOwner) {
+        let operator = meme.runtime.authenticated_signer().unwrap();
+        let other = team_member(meme_chain_id()).owner;
+        meme.runtime.set_chain_ownership(ChainOwnership::multiple(
+            vec![(operator, 100), (other, 100)],
+            u32::MAX,
+            TimeoutConfig::default(),
+        ));
+        // Operations proposed by owners are not called by any application
+        meme.runtime.set_authenticated_caller_id(None);
+        (operator, other)
+    }
+
+    fn leader_height(owner: AccountOwner, other: AccountOwner) -> BlockHeight {
+        let owner_weights = BTreeMap::from([(owner, 100), (other, 100)]);
+        (2..4)
+            .map(BlockHeight::from)
+            .find(|height| round_robin_leader(&owner_weights, *height, 0) == Some(owner))
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn propose_by_elected_leader() {
+        let mut meme = create_and_instantiate_meme().await;
+        let (operator, other) = set_meme_chain_owners(&mut meme);
+
+        meme.runtime
+            .set_block_height(leader_height(operator, other));
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        // Leader of next height is offline, its round times out
+        let height = BlockHeight::from(meme.runtime.block_height().0 + 1);
+        assert_eq!(meme.state.leader_for(height, 0).await.unwrap(), Some(other));
+        assert_eq!(
+            meme.state.leader_for(height, 1).await.unwrap(),
+            Some(operator)
+        );
+
+        meme.runtime.set_block_height(height);
+        meme.runtime.set_system_time(Timestamp::from(
+            TimeoutConfig::default().base_timeout.as_micros(),
+        ));
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("bbbb")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed OP: elect proposer: InvalidProposer")]
+    async fn propose_by_not_elected_leader() {
+        let mut meme = create_and_instantiate_meme().await;
+        let (operator, other) = set_meme_chain_owners(&mut meme);
+
+        meme.runtime
+            .set_block_height(leader_height(other, operator));
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn propose_by_stake_weighted_leader() {
+        let mut meme = create_and_instantiate_meme().await;
+        let (operator, other) = set_meme_chain_owners(&mut meme);
+        meme.state
+            .election_method
+            .set(LeaderElectionMethod::ProofOfStake);
+        let owner = Account {
+            chain_id: meme.runtime.chain_id(),
+            owner: operator,
+        };
+
+        meme.execute_message(MemeMessage::Stake {
+            owner,
+            amount: Amount::from_tokens(10),
+        })
+        .await;
+
+        // Only operator staked, so it's elected even at height of other owner in round robin
+        let height = leader_height(other, operator);
+        meme.runtime.set_block_height(height);
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        assert_eq!(
+            meme.state.leader_for(height, 0).await.unwrap(),
+            Some(operator)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn propose_by_round_robin_leader_after_first_round() {
+        let mut meme = create_and_instantiate_meme().await;
+        let (operator, other) = set_meme_chain_owners(&mut meme);
+        meme.state
+            .election_method
+            .set(LeaderElectionMethod::ProofOfStake);
+        let owner = Account {
+            chain_id: meme.runtime.chain_id(),
+            owner: operator,
+        };
+
+        meme.execute_message(MemeMessage::Stake {
+            owner,
+            amount: Amount::from_tokens(10),
+        })
+        .await;
+
+        let height = leader_height(other, operator);
+        meme.runtime.set_block_height(height);
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        // Sole staker is offline, the other owner still gets its round robin round
+        let next_height = BlockHeight::from(height.0 + 1);
+        let owner_weights = BTreeMap::from([(operator, 100), (other, 100)]);
+        let round = (1..8)
+            .find(|round| round_robin_leader(&owner_weights, next_height, *round) == Some(other))
+            .unwrap();
+        assert_eq!(
+            meme.state.leader_for(next_height, 0).await.unwrap(),
+            Some(operator)
+        );
+        assert_eq!(
+            meme.state.leader_for(next_height, round).await.unwrap(),
+            Some(other)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn propose_in_single_leader_round() {
+        let mut meme = create_and_instantiate_meme().await;
+        let (operator, other) = set_meme_chain_owners(&mut meme);
+        meme.state
+            .election_method
+            .set(LeaderElectionMethod::SingleLeaderRoundRobin);
+
+        // Leader of single leader round is checked by chain consensus
+        let height = leader_height(other, operator);
+        meme.runtime.set_block_height(height);
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        assert_eq!(meme.state.leader_for(height, 0).await.unwrap(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn propose_mined_block() {
+        let mut meme = create_and_instantiate_meme().await;
+        let (operator, _) = set_meme_chain_owners(&mut meme);
+        meme.state
+            .election_method
+            .set(LeaderElectionMethod::ProofOfWork);
+
+        meme.runtime.set_block_height(BlockHeight::from(2));
+        meme.execute_operation(MemeOperation::Mine {
+            nonce: CryptoHash::new(&TestString::new("aaaa")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        // Block is mined by its proposer, so it could contain other operations
+        meme.execute_operation(MemeOperation::Stake {
+            amount: Amount::from_tokens(10),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        assert_eq!(
+            meme.state.mining_info().miner.map(|miner| miner.owner),
+            Some(operator)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn submit_share_by_non_leader() {
+        let mut meme = create_and_instantiate_meme().await;
+        enable_mining_pool(&mut meme, 4);
+        let (operator, other) = set_meme_chain_owners(&mut meme);
+
+        // Operator is the round robin leader, other still submits its share
+        meme.runtime
+            .set_block_height(leader_height(operator, other));
+        meme.runtime.set_authenticated_signer(Some(other));
+        meme.execute_operation(MemeOperation::SubmitShare {
+            nonce: CryptoHash::new(&TestString::new("share 0")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        // Block is not mined by other, but share is verified by its own difficulty
+        meme.state
+            .election_method
+            .set(LeaderElectionMethod::ProofOfWork);
+        meme.execute_operation(MemeOperation::SubmitShare {
+            nonce: CryptoHash::new(&TestString::new("share 1")),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+
+        assert_eq!(meme.state.pplns_shares().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed OP: elect proposer: InvalidProposer")]
+    async fn propose_not_mined_block() {
+        let mut meme = create_and_instantiate_meme().await;
+        set_meme_chain_owners(&mut meme);
+        meme.state
+            .election_method
+            .set(LeaderElectionMethod::ProofOfWork);
+
+        meme.runtime.set_block_height(BlockHeight::from(2));
+        meme.execute_operation(MemeOperation::Stake {
+            amount: Amount::from_tokens(10),
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_stake_unstake() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let balance = meme.state.balance_of(owner).await;
+        let amount = Amount::from_tokens(50);
+
+        meme.execute_message(MemeMessage::Stake { owner, amount })
+            .await;
+
+        assert_eq!(
+            meme.events.last(),
+            Some(&MemeEvent::Stake { owner, amount })
+        );
+        assert_eq!(meme.state.stake_of(owner).await.unwrap(), amount);
+        assert_eq!(
+            meme.state.owner_stakes.get(&owner.owner).await.unwrap(),
+            Some(amount)
+        );
+        assert_eq!(meme.state.total_staked(), amount);
+        assert_eq!(
+            meme.state.balance_of(owner).await,
+            balance.try_sub(amount).unwrap()
+        );
+
+        meme.execute_message(MemeMessage::Unstake { owner, amount })
+            .await;
+
+        assert_eq!(
+            meme.events.last(),
+            Some(&MemeEvent::Unstake { owner, amount })
+        );
+        assert_eq!(meme.state.stake_of(owner).await.unwrap(), Amount::ZERO);
+        assert_eq!(
+            meme.state.owner_stakes.get(&owner.owner).await.unwrap(),
+            None
+        );
+        assert_eq!(meme.state.total_staked(), Amount::ZERO);
+        assert_eq!(meme.state.unbondings_of(owner).await.unwrap().len(), 1);
+
+        // Unbonding period of test meme is one second
+        meme.runtime.set_system_time(Timestamp::from(1_000_000));
+        meme.execute_message(MemeMessage::ClaimUnstaked { owner })
+            .await;
+
+        assert_eq!(
+            meme.events.last(),
+            Some(&MemeEvent::ClaimUnstaked { owner, amount })
+        );
+        assert_eq!(meme.state.balance_of(owner).await, balance);
+        assert_eq!(meme.state.unbondings_of(owner).await.unwrap().len(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_claim_unstaked_before_unbonded() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let amount = Amount::from_tokens(50);
+
+        meme.execute_message(MemeMessage::Stake { owner, amount })
+            .await;
+        meme.execute_message(MemeMessage::Unstake { owner, amount })
+            .await;
+        meme.execute_message(MemeMessage::ClaimUnstaked { owner })
+            .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InvalidAmount.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_stake_less_than_min_stake() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+
+        meme.execute_message(MemeMessage::Stake {
+            owner,
+            amount: Amount::from_tokens(1),
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InsufficientStake.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn user_chain_operation() {
+        let mut meme = create_and_instantiate_meme().await;
+        let to = Account {
+            chain_id: meme.runtime.chain_id(),
             owner: AccountOwner::from_str(
                 "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e03",
             )
-            .unwrap(),
+            .unwrap(),
+        };
+
+        let response = meme
+            .execute_operation(MemeOperation::Transfer {
+                to,
+                amount: Amount::from_tokens(1),
+            })
+            .now_or_never()
+            .expect("Execution of meme operation should not await anything");
+
+        assert!(matches!(response, MemeResponse::Ok));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_transfer() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let amount = owner_balance();
+
+        let to = other_user(meme_chain_id());
+
+        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
+        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
+        assert_eq!(balance, amount);
+
+        meme.execute_message(MemeMessage::Transfer { from, to, amount })
+            .await;
+
+        assert_eq!(meme.state.balances.contains_key(&to).await.unwrap(), true);
+        let balance = meme.state.balances.get(&to).await.unwrap().unwrap();
+        assert_eq!(balance, amount);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Insufficient balance")]
+    async fn message_transfer_insufficient_funds() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let amount = owner_balance();
+        let transfer_amount = amount.try_add(Amount::ONE).unwrap();
+
+        let to = other_user(meme_chain_id());
+
+        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
+        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
+        assert_eq!(balance, amount);
+
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to,
+            amount: transfer_amount,
+        })
+        .await;
+
+        assert_eq!(meme.state.balances.contains_key(&to).await.unwrap(), true);
+        let balance = meme.state.balances.get(&to).await.unwrap().unwrap();
+        assert_eq!(balance, amount);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_approve_owner_success() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+
+        let amount = owner_balance();
+        let allowance = Amount::from_tokens(22);
+
+        let spender = other_user(meme_chain_id());
+
+        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
+        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
+        assert_eq!(balance, amount);
+
+        meme.execute_message(MemeMessage::Approve {
+            owner: from,
+            spender,
+            amount: allowance,
+            expires_at: None,
+        })
+        .await;
+
+        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
+        assert_eq!(balance, amount.try_sub(allowance).unwrap());
+
+        assert_eq!(
+            meme.state.allowances.contains_key(&from).await.unwrap(),
+            true
+        );
+        assert_eq!(
+            meme.state
+                .allowances
+                .get(&from)
+                .await
+                .unwrap()
+                .unwrap()
+                .contains_key(&spender),
+            true
+        );
+        let balance = meme
+            .state
+            .allowances
+            .get(&from)
+            .await
+            .unwrap()
+            .unwrap()
+            .get(&spender)
+            .unwrap()
+            .amount;
+        assert_eq!(balance, allowance);
+
+        meme.execute_message(MemeMessage::Approve {
+            owner: from,
+            spender,
+            amount: allowance,
+            expires_at: None,
+        })
+        .await;
+
+        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
+        assert_eq!(
+            balance,
+            amount
+                .try_sub(allowance)
+                .unwrap()
+                .try_sub(allowance)
+                .unwrap()
+        );
+
+        let balance = meme
+            .state
+            .allowances
+            .get(&from)
+            .await
+            .unwrap()
+            .unwrap()
+            .get(&spender)
+            .unwrap()
+            .amount;
+        assert_eq!(balance, allowance.try_mul(2).unwrap());
+
+        let to = Account {
+            chain_id: meme.runtime.chain_id(),
+            owner: AccountOwner::from_str(
+                "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e08",
+            )
+            .unwrap(),
+        };
+
+        meme.execute_message(MemeMessage::TransferFrom {
+            owner: spender,
+            from,
+            to,
+            amount: allowance,
+        })
+        .await;
+
+        let balance = meme
+            .state
+            .allowances
+            .get(&from)
+            .await
+            .unwrap()
+            .unwrap()
+            .get(&spender)
+            .unwrap()
+            .amount;
+        assert_eq!(balance, allowance);
+
+        let balance = meme.state.balances.get(&to).await.unwrap().unwrap();
+        assert_eq!(balance, allowance);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_approve_insufficient_balance() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+
+        let amount = owner_balance();
+        let allowance = Amount::from_tokens(220);
+
+        let spender = other_user(meme_chain_id());
+
+        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
+        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
+        assert_eq!(balance, amount);
+
+        meme.execute_message(MemeMessage::Approve {
+            owner: from,
+            spender,
+            amount: allowance,
+            expires_at: None,
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InsufficientFunds.to_string())
+        );
+        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
+        assert_eq!(balance, amount);
+
+        assert_eq!(
+            meme.state.allowances.contains_key(&from).await.unwrap(),
+            false
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_approve_meme_owner_self_insufficient_balance() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+
+        let amount = owner_balance();
+        let allowance = Amount::from_tokens(220);
+
+        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
+        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
+        assert_eq!(balance, amount);
+
+        meme.execute_message(MemeMessage::Approve {
+            owner: from,
+            spender: from,
+            amount: allowance,
+            expires_at: None,
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InsufficientFunds.to_string())
+        );
+        assert_eq!(
+            meme.state.allowances.contains_key(&from).await.unwrap(),
+            false
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_transfer_ownership() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let new_owner = other_user(meme_chain_id());
+
+        // It won't panic here, it'll approved from application balance
+        meme.execute_message(MemeMessage::TransferOwnership { owner, new_owner })
+            .await;
+
+        assert_eq!(meme.state.owner.get().unwrap(), new_owner);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_burn() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let balance = owner_balance();
+        let total_supply = meme.state.meme().total_supply;
+        let amount = Amount::from_tokens(22);
+
+        meme.execute_message(MemeMessage::Burn { owner, amount })
+            .await;
+
+        assert_eq!(
+            meme.state.balance_of(owner).await,
+            balance.try_sub(amount).unwrap()
+        );
+        assert_eq!(
+            meme.state.meme().total_supply,
+            total_supply.try_sub(amount).unwrap()
+        );
+        assert_eq!(meme.state.burned(), amount);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_burn_insufficient_funds() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let amount = owner_balance().try_add(Amount::ONE).unwrap();
+
+        meme.execute_message(MemeMessage::Burn { owner, amount })
+            .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InsufficientFunds.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_burn_from() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let spender = other_user(meme_chain_id());
+        let balance = owner_balance();
+        let total_supply = meme.state.meme().total_supply;
+        let allowance = Amount::from_tokens(22);
+        let amount = Amount::from_tokens(20);
+
+        meme.execute_message(MemeMessage::Approve {
+            owner: from,
+            spender,
+            amount: allowance,
+            expires_at: None,
+        })
+        .await;
+        meme.execute_message(MemeMessage::BurnFrom {
+            owner: spender,
+            from,
+            amount,
+        })
+        .await;
+
+        // Allowance is deducted from balance when it's approved
+        assert_eq!(
+            meme.state.balance_of(from).await,
+            balance.try_sub(allowance).unwrap()
+        );
+        assert_eq!(
+            meme.state
+                .allowances
+                .get(&from)
+                .await
+                .unwrap()
+                .unwrap()
+                .get(&spender)
+                .unwrap()
+                .amount,
+            allowance.try_sub(amount).unwrap()
+        );
+        assert_eq!(
+            meme.state.meme().total_supply,
+            total_supply.try_sub(amount).unwrap()
+        );
+        assert_eq!(meme.state.burned(), amount);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_burn_from_insufficient_allowance() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let spender = other_user(meme_chain_id());
+
+        meme.execute_message(MemeMessage::Approve {
+            owner: from,
+            spender,
+            amount: Amount::from_tokens(22),
+            expires_at: None,
+        })
+        .await;
+        meme.execute_message(MemeMessage::BurnFrom {
+            owner: spender,
+            from,
+            amount: Amount::from_tokens(23),
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InsufficientAllowance.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_claim_vested() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let balance = owner_balance();
+
+        // Creator allocation is linearly vested in 10 seconds
+        meme.runtime.set_system_time(Timestamp::from(5_000_000));
+        meme.execute_message(MemeMessage::ClaimVested { owner })
+            .await;
+
+        let claimed = Amount::from_tokens(500);
+        assert_eq!(
+            meme.events.last(),
+            Some(&MemeEvent::ClaimVested {
+                owner,
+                amount: claimed
+            })
+        );
+        assert_eq!(
+            meme.state.balance_of(owner).await,
+            balance.try_add(claimed).unwrap()
+        );
+        assert_eq!(
+            meme.state.vesting_of(owner).await.unwrap().unwrap().claimed,
+            claimed
+        );
+
+        meme.runtime.set_system_time(Timestamp::from(20_000_000));
+        meme.execute_message(MemeMessage::ClaimVested { owner })
+            .await;
+
+        assert_eq!(
+            meme.state.balance_of(owner).await,
+            balance.try_add(Amount::from_tokens(1000)).unwrap()
+        );
+        assert_eq!(
+            meme.state
+                .vesting_claimable(owner, Timestamp::from(30_000_000))
+                .await
+                .unwrap(),
+            Amount::ZERO
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_claim_team_vested() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = team_member(meme_chain_id());
+
+        // Team allocation is released by 100 tokens each second
+        meme.runtime.set_system_time(Timestamp::from(2_500_000));
+        meme.execute_message(MemeMessage::ClaimVested { owner })
+            .await;
+
+        assert_eq!(meme.state.balance_of(owner).await, Amount::from_tokens(200));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_claim_vested_before_cliff() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+
+        meme.runtime.set_system_time(Timestamp::from(999_999));
+        meme.execute_message(MemeMessage::ClaimVested { owner })
+            .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InvalidAmount.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_claim_vested_without_vesting() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = other_user(meme_chain_id());
+
+        meme.runtime.set_system_time(Timestamp::from(20_000_000));
+        meme.execute_message(MemeMessage::ClaimVested { owner })
+            .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::NoVesting.to_string())
+        );
+    }
+
+    fn presale_config(chain_id: ChainId) -> PresaleConfig {
+        let buyer = Account {
+            chain_id,
+            owner: operator(),
+        };
+        PresaleConfig {
+            whitelist: vec![WhitelistAllocation {
+                account: buyer,
+                cap: Amount::from_tokens(1000),
+            }],
+            // One native token buys 100 meme tokens
+            price: Amount::from_millis(10),
+            ends_at: Timestamp::from(1_000_000),
+            release_at: Timestamp::from(2_000_000),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_presale_buy() {
+        let chain_id = meme_chain_id();
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            presale: Some(presale_config(chain_id)),
+            ..Default::default()
+        })
+        .await;
+        let owner = meme.owner_account();
+        let balance = meme.state.balance_of(owner).await;
+
+        meme.execute_message(MemeMessage::PresaleBuy {
+            owner,
+            amount: Amount::from_tokens(2),
+        })
+        .await;
+
+        // Purchased tokens are locked
+        assert_eq!(meme.state.balance_of(owner).await, balance);
+        assert_eq!(
+            meme.state.presale_purchase_of(owner).await.unwrap(),
+            Amount::from_tokens(200)
+        );
+        let presale = meme.state.presale().unwrap();
+        assert_eq!(presale.sold, Amount::from_tokens(200));
+        assert_eq!(presale.raised, Amount::from_tokens(2));
+        assert_eq!(presale.reserved, Amount::from_tokens(1000));
+    }
+
+    // Rejected buy doesn't fail the message, paid native tokens are refunded to buyer instead
+    async fn execute_rejected_presale_buy(meme: &mut MemeContract, owner: Account, amount: Amount) {
+        let application = AccountOwner::from(meme.runtime.application_id().forget_abi());
+        let application_balance = meme.runtime.owner_balance(application);
+        let owner_balance = meme.runtime.owner_balance(owner.owner);
+        let presale = meme.state.presale().unwrap();
+
+        meme.execute_message(MemeMessage::PresaleBuy { owner, amount })
+            .await;
+
+        assert_eq!(
+            meme.runtime.owner_balance(application),
+            application_balance.try_sub(amount).unwrap()
+        );
+        assert_eq!(
+            meme.runtime.owner_balance(owner.owner),
+            owner_balance.try_add(amount).unwrap()
+        );
+        assert_eq!(
+            meme.state.presale_purchase_of(owner).await.unwrap(),
+            Amount::ZERO
+        );
+        assert_eq!(meme.state.presale().unwrap(), presale);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_presale_buy_exceed_allocation() {
+        let chain_id = meme_chain_id();
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            presale: Some(presale_config(chain_id)),
+            ..Default::default()
+        })
+        .await;
+        let owner = meme.owner_account();
+
+        execute_rejected_presale_buy(&mut meme, owner, Amount::from_tokens(11)).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_presale_buy_not_whitelisted() {
+        let chain_id = meme_chain_id();
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            presale: Some(presale_config(chain_id)),
+            ..Default::default()
+        })
+        .await;
+        let owner = team_member(chain_id);
+        meme.runtime.set_owner_balance(owner.owner, Amount::ZERO);
+
+        execute_rejected_presale_buy(&mut meme, owner, Amount::from_tokens(1)).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_presale_buy_after_ended() {
+        let chain_id = meme_chain_id();
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            presale: Some(presale_config(chain_id)),
+            ..Default::default()
+        })
+        .await;
+        let owner = meme.owner_account();
+
+        meme.runtime.set_system_time(Timestamp::from(1_000_000));
+        execute_rejected_presale_buy(&mut meme, owner, Amount::from_tokens(1)).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_presale_buy_bounced() {
+        let chain_id = meme_chain_id();
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            presale: Some(presale_config(chain_id)),
+            ..Default::default()
+        })
+        .await;
+        let owner = meme.owner_account();
+        let amount = Amount::from_tokens(11);
+        let application = AccountOwner::from(meme.runtime.application_id().forget_abi());
+        let owner_balance = meme.runtime.owner_balance(owner.owner);
+        meme.state
+            .add_pending_operation(
+                owner,
+                PendingOperationKind::PresaleBuy,
+                owner,
+                None,
+                amount,
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+        // Paid funds are held by application on creation chain
+        meme.runtime.set_owner_balance(application, amount);
+
+        meme.runtime.set_message_is_bouncing(Some(true));
+        meme.execute_message(MemeMessage::PresaleBuy { owner, amount })
+            .await;
+
+        let operations = meme.state.pending_operations_of(owner).await.unwrap();
+        assert_eq!(operations[0].status, PendingOperationStatus::Failed);
+        // Refund is sent to creation chain where paid funds are held
+        let (refunded, refund) = match &meme
+            .runtime
+            .created_send_message_requests()
+            .last()
+            .unwrap()
+            .message
+        {
+            MemeMessage::RefundPresaleBuy { owner, amount } => (*owner, *amount),
+            _ => panic!("Invalid message"),
+        };
+        assert_eq!(refunded, owner);
+        assert_eq!(refund, amount);
+
+        meme.runtime.set_message_is_bouncing(Some(false));
+        meme.execute_message(MemeMessage::RefundPresaleBuy {
+            owner: refunded,
+            amount: refund,
+        })
+        .await;
+
+        assert_eq!(meme.runtime.owner_balance(application), Amount::ZERO);
+        assert_eq!(
+            meme.runtime.owner_balance(owner.owner),
+            owner_balance.try_add(amount).unwrap()
+        );
+        assert_eq!(
+            meme.state.presale_purchase_of(owner).await.unwrap(),
+            Amount::ZERO
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn close_presale_and_claim() {
+        let chain_id = meme_chain_id();
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            presale: Some(presale_config(chain_id)),
+            ..Default::default()
+        })
+        .await;
+        let owner = meme.owner_account();
+        let application = meme.application_account();
+        let balance = meme.state.balance_of(owner).await;
+
+        meme.execute_message(MemeMessage::PresaleBuy {
+            owner,
+            amount: Amount::from_tokens(2),
+        })
+        .await;
+
+        let application_balance = meme.state.balance_of(application).await;
+        let signer_balance = meme.runtime.owner_balance(owner.owner);
+        meme.runtime.set_system_time(Timestamp::from(1_000_000));
+        let response = meme.execute_operation(MemeOperation::ClosePresale).await;
+        assert!(matches!(response, MemeResponse::Ok));
+
+        // Pool is funded by application, not by whoever closes the presale
+        assert_eq!(meme.runtime.owner_balance(owner.owner), signer_balance);
+
+        // Unsold tokens are returned to application
+        assert!(meme.state.presale().unwrap().closed);
+        assert_eq!(
+            meme.state.balance_of(application).await,
+            application_balance
+                .try_add(Amount::from_tokens(800))
+                .unwrap()
+        );
+
+        meme.runtime.set_system_time(Timestamp::from(2_000_000));
+        meme.execute_message(MemeMessage::ClaimPresale { owner })
+            .await;
+
+        assert_eq!(
+            meme.events.last(),
+            Some(&MemeEvent::ClaimPresale {
+                owner,
+                amount: Amount::from_tokens(200)
+            })
+        );
+        assert_eq!(
+            meme.state.balance_of(owner).await,
+            balance.try_add(Amount::from_tokens(200)).unwrap()
+        );
+        assert_eq!(
+            meme.state.presale_purchase_of(owner).await.unwrap(),
+            Amount::ZERO
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_claim_presale_locked() {
+        let chain_id = meme_chain_id();
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            presale: Some(presale_config(chain_id)),
+            ..Default::default()
+        })
+        .await;
+        let owner = meme.owner_account();
+
+        meme.execute_message(MemeMessage::PresaleBuy {
+            owner,
+            amount: Amount::from_tokens(2),
+        })
+        .await;
+
+        meme.runtime.set_system_time(Timestamp::from(1_999_999));
+        meme.execute_message(MemeMessage::ClaimPresale { owner })
+            .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::PresaleLocked.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_claim_presale_not_closed() {
+        let chain_id = meme_chain_id();
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            presale: Some(presale_config(chain_id)),
+            ..Default::default()
+        })
+        .await;
+        let owner = meme.owner_account();
+
+        meme.execute_message(MemeMessage::PresaleBuy {
+            owner,
+            amount: Amount::from_tokens(2),
+        })
+        .await;
+
+        meme.runtime.set_system_time(Timestamp::from(2_000_000));
+        meme.execute_message(MemeMessage::ClaimPresale { owner })
+            .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::PresaleNotClosed.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn instantiate_with_equal_airdrop() {
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            genesis_miner_airdrop: Some(GenesisMinerAirdrop {
+                amount: Amount::from_tokens(1000),
+                split: AirdropSplit::Equal,
+                weights: vec![],
+                vesting: None,
+            }),
+            ..Default::default()
+        })
+        .await;
+        let owner = meme.owner_account();
+        let miner = team_member(meme_chain_id());
+
+        assert_eq!(meme.state.airdropped(), Amount::from_tokens(1000));
+        assert_eq!(
+            meme.state.balance_of(owner).await,
+            owner_balance().try_add(Amount::from_tokens(500)).unwrap()
+        );
+        assert_eq!(meme.state.balance_of(miner).await, Amount::from_tokens(500));
+        for miner in [owner, miner] {
+            assert!(meme.events.contains(&MemeEvent::Airdrop {
+                miner,
+                amount: Amount::from_tokens(500),
+                vested: false,
+            }));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn instantiate_airdrop_without_proxy() {
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            genesis_miner_airdrop: Some(GenesisMinerAirdrop {
+                amount: Amount::from_tokens(1000),
+                split: AirdropSplit::Equal,
+                weights: vec![],
+                vesting: None,
+            }),
+            without_proxy: true,
+            ..Default::default()
+        })
+        .await;
+        let miner = team_member(meme_chain_id());
+
+        // Genesis miners from instantiation argument are not approved by proxy
+        assert_eq!(meme.state.airdropped(), Amount::ZERO);
+        assert_eq!(meme.state.balance_of(miner).await, Amount::ZERO);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn claim_vested_airdrop() {
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            genesis_miner_airdrop: Some(GenesisMinerAirdrop {
+                amount: Amount::from_tokens(1000),
+                split: AirdropSplit::Vested,
+                weights: vec![],
+                vesting: Some(VestingSchedule {
+                    amount: Amount::ZERO,
+                    cliff_ms: 0,
+                    duration_ms: 1000,
+                    release: VestingRelease::Linear,
+                    step_ms: 0,
+                }),
+            }),
+            ..Default::default()
+        })
+        .await;
+        let miner = team_member(meme_chain_id());
+
+        // Vested airdrop is not credited to balance
+        assert_eq!(meme.state.balance_of(miner).await, Amount::ZERO);
+        assert!(meme.events.contains(&MemeEvent::Airdrop {
+            miner,
+            amount: Amount::from_tokens(500),
+            vested: true,
+        }));
+        assert_eq!(
+            meme.state
+                .airdrop_vesting_of(miner)
+                .await
+                .unwrap()
+                .unwrap()
+                .schedule
+                .amount,
+            Amount::from_tokens(500)
+        );
+
+        // Team allocation of the same miner is not released in the first step
+        meme.runtime.set_system_time(Timestamp::from(500_000));
+        meme.execute_message(MemeMessage::ClaimVested { owner: miner })
+            .await;
+
+        assert_eq!(meme.state.balance_of(miner).await, Amount::from_tokens(250));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_decrease_and_revoke_allowance() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let spender = other_user(meme_chain_id());
+        let balance = meme.state.balance_of(owner).await;
+
+        meme.execute_message(MemeMessage::Approve {
+            owner,
+            spender,
+            amount: Amount::from_tokens(22),
+            expires_at: None,
+        })
+        .await;
+        meme.execute_message(MemeMessage::DecreaseAllowance {
+            owner,
+            spender,
+            amount: Amount::from_tokens(20),
+        })
+        .await;
+
+        assert_eq!(
+            meme.events.last(),
+            Some(&MemeEvent::DecreaseAllowance {
+                owner,
+                spender,
+                amount: Amount::from_tokens(20)
+            })
+        );
+        assert_eq!(
+            meme.state.allowance_of(owner, spender).await,
+            Amount::from_tokens(2)
+        );
+        assert_eq!(
+            meme.state.balance_of(owner).await,
+            balance.try_sub(Amount::from_tokens(2)).unwrap()
+        );
+        assert_eq!(
+            meme.state.allowances_of(owner).await.unwrap(),
+            vec![Allowance {
+                spender,
+                amount: Amount::from_tokens(2),
+                expires_at: None,
+            }]
+        );
+
+        meme.execute_message(MemeMessage::RevokeAllowance { owner, spender })
+            .await;
+
+        assert_eq!(
+            meme.events.last(),
+            Some(&MemeEvent::RevokeAllowance {
+                owner,
+                spender,
+                amount: Amount::from_tokens(2)
+            })
+        );
+        assert_eq!(meme.state.balance_of(owner).await, balance);
+        assert_eq!(meme.state.allowances_of(owner).await.unwrap(), vec![]);
+        assert_eq!(
+            meme.state.allowances.contains_key(&owner).await.unwrap(),
+            false
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_decrease_allowance_insufficient() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let spender = other_user(meme_chain_id());
+
+        meme.execute_message(MemeMessage::Approve {
+            owner,
+            spender,
+            amount: Amount::from_tokens(22),
+            expires_at: None,
+        })
+        .await;
+        meme.execute_message(MemeMessage::DecreaseAllowance {
+            owner,
+            spender,
+            amount: Amount::from_tokens(23),
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InsufficientAllowance.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_revoke_allowance_without_approval() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+
+        meme.execute_message(MemeMessage::RevokeAllowance {
+            owner,
+            spender: team_member(meme_chain_id()),
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InsufficientAllowance.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_transfer_from_expired_allowance() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let spender = other_user(meme_chain_id());
+
+        meme.execute_message(MemeMessage::Approve {
+            owner: from,
+            spender,
+            amount: Amount::from_tokens(22),
+            expires_at: Some(Timestamp::from(1_000_000)),
+        })
+        .await;
+
+        meme.runtime.set_system_time(Timestamp::from(1_000_000));
+        meme.execute_message(MemeMessage::TransferFrom {
+            owner: spender,
+            from,
+            to: team_member(meme_chain_id()),
+            amount: Amount::from_tokens(1),
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some("Allowance expired".to_string())
+        );
+        assert_eq!(
+            meme.state.balance_of(team_member(meme_chain_id())).await,
+            Amount::ZERO
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_approve_expired() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+
+        meme.runtime.set_system_time(Timestamp::from(1_000_000));
+        meme.execute_message(MemeMessage::Approve {
+            owner,
+            spender: team_member(meme_chain_id()),
+            amount: Amount::from_tokens(22),
+            expires_at: Some(Timestamp::from(1_000_000)),
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InvalidExpiry.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_reclaim_expired_allowance() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let spender = other_user(meme_chain_id());
+        let to = team_member(meme_chain_id());
+        let balance = meme.state.balance_of(from).await;
+
+        meme.execute_message(MemeMessage::Approve {
+            owner: from,
+            spender,
+            amount: Amount::from_tokens(22),
+            expires_at: Some(Timestamp::from(1_000_000)),
+        })
+        .await;
+
+        // Allowance could be spent before expiry
+        meme.runtime.set_system_time(Timestamp::from(999_999));
+        meme.execute_message(MemeMessage::TransferFrom {
+            owner: spender,
+            from,
+            to,
+            amount: Amount::from_tokens(2),
+        })
+        .await;
+        assert_eq!(meme.state.balance_of(to).await, Amount::from_tokens(2));
+
+        // Escrow is returned to owner when it spends after expiry
+        meme.runtime.set_system_time(Timestamp::from(1_000_000));
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to,
+            amount: balance.try_sub(Amount::from_tokens(2)).unwrap(),
+        })
+        .await;
+
+        assert_eq!(meme.state.balance_of(from).await, Amount::ZERO);
+        assert_eq!(meme.state.balance_of(to).await, balance);
+        assert_eq!(meme.state.allowances_of(from).await.unwrap(), vec![]);
+        assert!(meme.events.contains(&MemeEvent::ReclaimAllowance {
+            owner: from,
+            amount: Amount::from_tokens(20),
+        }));
+    }
+
+    fn signed_permit(secret_key: &AccountSecretKey, nonce: u64) -> (Permit, AccountSignature) {
+        let permit = Permit {
+            token: meme_application_id(),
+            owner: Account {
+                chain_id: meme_chain_id(),
+                owner: AccountOwner::from(secret_key.public()),
+            },
+            spender: team_member(meme_chain_id()),
+            amount: Amount::from_tokens(10),
+            nonce,
+            deadline: Timestamp::from(1_000_000),
+            expires_at: None,
+        };
+        let signature = secret_key.sign(&permit);
+        (permit, signature)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn operation_permit() {
+        let mut meme = create_and_instantiate_meme().await;
+        let secret_key = AccountSecretKey::Ed25519(Ed25519SecretKey::generate());
+        let (permit, signature) = signed_permit(&secret_key, 0);
+
+        let response = meme
+            .execute_operation(MemeOperation::Permit {
+                permit,
+                public_key: secret_key.public(),
+                signature,
+            })
+            .now_or_never()
+            .expect("Execution of meme operation should not await anything");
+
+        assert!(matches!(response, MemeResponse::Ok));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed OP: permit: InvalidSignature")]
+    async fn operation_permit_invalid_signature() {
+        let mut meme = create_and_instantiate_meme().await;
+        let secret_key = AccountSecretKey::Ed25519(Ed25519SecretKey::generate());
+        let (permit, _) = signed_permit(&secret_key, 0);
+
+        // Signed by another key
+        let other_key = AccountSecretKey::Ed25519(Ed25519SecretKey::generate());
+        let signature = other_key.sign(&permit);
+
+        meme.execute_operation(MemeOperation::Permit {
+            permit,
+            public_key: secret_key.public(),
+            signature,
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed OP: permit: InvalidPermitToken")]
+    async fn operation_permit_other_token() {
+        let mut meme = create_and_instantiate_meme().await;
+        let secret_key = AccountSecretKey::Ed25519(Ed25519SecretKey::generate());
+        let (mut permit, _) = signed_permit(&secret_key, 0);
+
+        permit.token = meme.state.swap_application_id().unwrap();
+        let signature = secret_key.sign(&permit);
+
+        meme.execute_operation(MemeOperation::Permit {
+            permit,
+            public_key: secret_key.public(),
+            signature,
+        })
+        .now_or_never()
+        .expect("Execution of meme operation should not await anything");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_permit() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let spender = team_member(meme_chain_id());
+        let balance = meme.state.balance_of(owner).await;
+
+        meme.execute_message(MemeMessage::Permit {
+            caller: spender,
+            permit: Permit {
+                token: meme_application_id(),
+                owner,
+                spender,
+                amount: Amount::from_tokens(10),
+                nonce: 0,
+                deadline: Timestamp::from(1_000_000),
+                expires_at: Some(Timestamp::from(2_000_000)),
+            },
+        })
+        .await;
+
+        assert_eq!(meme.state.permit_nonce(owner).await.unwrap(), 1);
+        assert_eq!(
+            meme.state.allowances_of(owner).await.unwrap(),
+            vec![Allowance {
+                spender,
+                amount: Amount::from_tokens(10),
+                expires_at: Some(Timestamp::from(2_000_000)),
+            }]
+        );
+        assert_eq!(
+            meme.state.balance_of(owner).await,
+            balance.try_sub(Amount::from_tokens(10)).unwrap()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_permit_replayed() {
+        let mut meme = create_and_instantiate_meme().await;
+        let permit = Permit {
+            token: meme_application_id(),
+            owner: meme.owner_account(),
+            spender: team_member(meme_chain_id()),
+            amount: Amount::from_tokens(10),
+            nonce: 0,
+            deadline: Timestamp::from(1_000_000),
+            expires_at: None,
         };
+        let caller = team_member(meme_chain_id());
+
+        meme.execute_message(MemeMessage::Permit {
+            caller,
+            permit: permit.clone(),
+        })
+        .await;
+        meme.execute_message(MemeMessage::Permit { caller, permit })
+            .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InvalidPermitNonce.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_permit_expired() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+
+        meme.runtime.set_system_time(Timestamp::from(1_000_000));
+        meme.execute_message(MemeMessage::Permit {
+            caller: team_member(meme_chain_id()),
+            permit: Permit {
+                token: meme_application_id(),
+                owner,
+                spender: team_member(meme_chain_id()),
+                amount: Amount::from_tokens(10),
+                nonce: 0,
+                deadline: Timestamp::from(1_000_000),
+                expires_at: None,
+            },
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::PermitExpired.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_permit_insufficient_balance() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let spender = team_member(meme_chain_id());
+        let balance = meme.state.balance_of(owner).await;
+
+        meme.execute_message(MemeMessage::Permit {
+            caller: spender,
+            permit: Permit {
+                token: meme_application_id(),
+                owner,
+                spender,
+                amount: balance.try_add(Amount::ONE).unwrap(),
+                nonce: 0,
+                deadline: Timestamp::from(1_000_000),
+                expires_at: None,
+            },
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InsufficientFunds.to_string())
+        );
+        // Rejected permit doesn't consume the nonce
+        assert_eq!(meme.state.permit_nonce(owner).await.unwrap(), 0);
+        assert_eq!(meme.state.balance_of(owner).await, balance);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_permit_keeps_stricter_expiry() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let spender = team_member(meme_chain_id());
+
+        meme.execute_message(MemeMessage::Approve {
+            owner,
+            spender,
+            amount: Amount::from_tokens(10),
+            expires_at: Some(Timestamp::from(1_000_000)),
+        })
+        .await;
+        // Neither approval without expiry nor a later expiry extends the allowance
+        meme.execute_message(MemeMessage::Approve {
+            owner,
+            spender,
+            amount: Amount::from_tokens(10),
+            expires_at: None,
+        })
+        .await;
+        meme.execute_message(MemeMessage::Permit {
+            caller: spender,
+            permit: Permit {
+                token: meme_application_id(),
+                owner,
+                spender,
+                amount: Amount::from_tokens(10),
+                nonce: 0,
+                deadline: Timestamp::from(1_000_000),
+                expires_at: Some(Timestamp::from(2_000_000)),
+            },
+        })
+        .await;
+
+        assert_eq!(
+            meme.state.allowances_of(owner).await.unwrap(),
+            vec![Allowance {
+                spender,
+                amount: Amount::from_tokens(30),
+                expires_at: Some(Timestamp::from(1_000_000)),
+            }]
+        );
+    }
+
+    #[test]
+    fn cross_application_call() {}
+
+    fn mock_application_call(
+        _authenticated: bool,
+        _application_id: ApplicationId,
+        _operation: Vec<u8>,
+    ) -> Vec<u8> {
+        bcs::to_bytes(&SwapResponse::ChainId(meme_chain_id())).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_batch_transfer() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let to_1 = team_member(meme_chain_id());
+        let to_2 = other_user(meme_chain_id());
+        let balance = meme.state.balance_of(from).await;
+
+        meme.execute_message(MemeMessage::BatchTransfer {
+            from,
+            transfers: vec![
+                BatchTransferItem {
+                    to: to_1,
+                    amount: Amount::from_tokens(3),
+                },
+                BatchTransferItem {
+                    to: to_2,
+                    amount: Amount::from_tokens(5),
+                },
+                BatchTransferItem {
+                    to: to_1,
+                    amount: Amount::from_tokens(2),
+                },
+            ],
+        })
+        .await;
+
+        assert_eq!(meme.state.balance_of(to_1).await, Amount::from_tokens(5));
+        assert_eq!(meme.state.balance_of(to_2).await, Amount::from_tokens(5));
+        assert_eq!(
+            meme.state.balance_of(from).await,
+            balance.try_sub(Amount::from_tokens(10)).unwrap()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_batch_transfer_insufficient_funds() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let balance = meme.state.balance_of(from).await;
+
+        // Each transfer is affordable but the total is not
+        meme.execute_message(MemeMessage::BatchTransfer {
+            from,
+            transfers: vec![
+                BatchTransferItem {
+                    to: team_member(meme_chain_id()),
+                    amount: balance,
+                },
+                BatchTransferItem {
+                    to: team_member(meme_chain_id()),
+                    amount: Amount::from_tokens(1),
+                },
+            ],
+        })
+        .await;
 
-        let response = meme
-            .execute_operation(MemeOperation::Transfer {
-                to,
-                amount: Amount::from_tokens(1),
-            })
-            .now_or_never()
-            .expect("Execution of meme operation should not await anything");
+        // Nothing is transferred when the batch fails
+        assert_eq!(
+            replied_failure(&meme),
+            Some("Insufficient funds".to_string())
+        );
+        assert_eq!(meme.state.balance_of(from).await, balance);
+        assert_eq!(
+            meme.state.balance_of(team_member(meme_chain_id())).await,
+            Amount::ZERO
+        );
+    }
 
-        assert!(matches!(response, MemeResponse::Ok));
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_batch_transfer_empty() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+
+        meme.execute_message(MemeMessage::BatchTransfer {
+            from,
+            transfers: vec![],
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some("Invalid batch size".to_string())
+        );
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn message_transfer() {
+    async fn operation_transfer_pending() {
         let mut meme = create_and_instantiate_meme().await;
-        let from = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: meme.runtime.authenticated_signer().unwrap(),
-        };
-        let amount = meme.state.initial_owner_balance().await;
+        let from = meme.owner_account();
+        let to = team_member(meme_chain_id());
+        let amount = Amount::from_tokens(1);
 
-        let to = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: AccountOwner::from_str(
-                "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc8f",
-            )
-            .unwrap(),
-        };
+        meme.execute_operation(MemeOperation::Transfer { to, amount })
+            .await;
 
-        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
-        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
-        assert_eq!(balance, amount);
+        assert!(
+            meme.runtime
+                .created_send_message_requests()
+                .last()
+                .unwrap()
+                .is_tracked
+        );
+        let operations = meme.state.pending_operations_of(from).await.unwrap();
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0].kind, PendingOperationKind::Transfer);
+        assert_eq!(operations[0].to, Some(to));
+        assert_eq!(operations[0].amount, amount);
+        assert_eq!(operations[0].status, PendingOperationStatus::Pending);
+
+        meme.execute_message(MemeMessage::OperationFailed {
+            owner: from,
+            reason: "Insufficient funds".to_string(),
+        })
+        .await;
+
+        let operations = meme.state.pending_operations_of(from).await.unwrap();
+        assert_eq!(operations[0].status, PendingOperationStatus::Failed);
+        assert_eq!(operations[0].reason, Some("Insufficient funds".to_string()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_transfer_replied() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let balance = meme.state.balance_of(from).await;
+
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to: team_member(meme_chain_id()),
+            amount: balance.try_add(Amount::ONE).unwrap(),
+        })
+        .await;
+        assert_eq!(
+            replied_failure(&meme),
+            Some("Insufficient funds".to_string())
+        );
+
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to: team_member(meme_chain_id()),
+            amount: balance,
+        })
+        .await;
+        assert!(matches!(
+            meme.runtime
+                .created_send_message_requests()
+                .last()
+                .unwrap()
+                .message,
+            MemeMessage::OperationSucceeded { owner } if owner == from
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_transfer_bounced() {
+        let mut meme = create_and_instantiate_meme().await;
+        let from = meme.owner_account();
+        let to = team_member(meme_chain_id());
+        let amount = Amount::from_tokens(1);
+
+        meme.execute_operation(MemeOperation::Transfer { to, amount })
+            .await;
 
+        meme.runtime.set_message_is_bouncing(Some(true));
         meme.execute_message(MemeMessage::Transfer { from, to, amount })
             .await;
 
-        assert_eq!(meme.state.balances.contains_key(&to).await.unwrap(), true);
-        let balance = meme.state.balances.get(&to).await.unwrap().unwrap();
-        assert_eq!(balance, amount);
+        // Bounced message is not executed as a transfer
+        let operations = meme.state.pending_operations_of(from).await.unwrap();
+        assert_eq!(operations[0].status, PendingOperationStatus::Failed);
+        assert_eq!(meme.state.balance_of(to).await, Amount::ZERO);
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    #[should_panic(expected = "Insufficient balance")]
-    async fn message_transfer_insufficient_funds() {
+    async fn operation_stake_pending() {
         let mut meme = create_and_instantiate_meme().await;
-        let from = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: meme.runtime.authenticated_signer().unwrap(),
-        };
-        let amount = meme.state.initial_owner_balance().await;
-        let transfer_amount = amount.try_add(Amount::ONE).unwrap();
+        let owner = meme.owner_account();
+        let amount = Amount::from_tokens(1);
 
-        let to = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: AccountOwner::from_str(
-                "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc8f",
+        meme.execute_operation(MemeOperation::Stake { amount })
+            .await;
+
+        assert!(
+            meme.runtime
+                .created_send_message_requests()
+                .last()
+                .unwrap()
+                .is_tracked
+        );
+        let operations = meme.state.pending_operations_of(owner).await.unwrap();
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0].kind, PendingOperationKind::Stake);
+        assert_eq!(operations[0].amount, amount);
+        assert_eq!(operations[0].status, PendingOperationStatus::Pending);
+
+        // Stake less than minimum stake is replied to sender chain instead of rejected
+        meme.execute_message(MemeMessage::Stake { owner, amount })
+            .await;
+        let reason = replied_failure(&meme).unwrap();
+        assert_eq!(reason, MemeError::InsufficientStake.to_string());
+
+        meme.execute_message(MemeMessage::OperationFailed { owner, reason })
+            .await;
+
+        let operations = meme.state.pending_operations_of(owner).await.unwrap();
+        assert_eq!(operations[0].status, PendingOperationStatus::Failed);
+        assert_eq!(
+            operations[0].reason,
+            Some(MemeError::InsufficientStake.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_permit_bounced() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let caller = team_member(meme_chain_id());
+        let permit = Permit {
+            token: meme_application_id(),
+            owner,
+            spender: caller,
+            amount: Amount::from_tokens(10),
+            nonce: 0,
+            deadline: Timestamp::from(1_000_000),
+            expires_at: None,
+        };
+        meme.state
+            .add_pending_operation(
+                caller,
+                PendingOperationKind::Permit,
+                owner,
+                Some(caller),
+                permit.amount,
+                Timestamp::from(0),
             )
-            .unwrap(),
+            .await
+            .unwrap();
+
+        meme.runtime.set_message_is_bouncing(Some(true));
+        meme.execute_message(MemeMessage::Permit { caller, permit })
+            .await;
+
+        // Submitter's log is resolved, permit is not applied
+        let operations = meme.state.pending_operations_of(caller).await.unwrap();
+        assert_eq!(operations[0].status, PendingOperationStatus::Failed);
+        assert_eq!(meme.state.permit_nonce(owner).await.unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn holders_index() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.owner_account();
+        let to = team_member(meme_chain_id());
+        let amount = Amount::from_tokens(5);
+        let holder_count = meme.state.holder_count();
+
+        meme.execute_message(MemeMessage::Transfer {
+            from: owner,
+            to,
+            amount,
+        })
+        .await;
+        assert_eq!(meme.state.holder_count(), holder_count + 1);
+
+        let holders = meme
+            .state
+            .holders(usize::MAX, None, Amount::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(holders.len() as u64, meme.state.holder_count());
+        assert!(holders
+            .windows(2)
+            .all(|pair| pair[0].1.balance >= pair[1].1.balance));
+        assert!(holders.contains(&(
+            Holder {
+                account: to,
+                balance: amount,
+            },
+            HolderCursor {
+                account: to,
+                balance: amount,
+            }
+        )));
+
+        // Each page starts right after cursor, holders of the same balance included
+        meme.execute_message(MemeMessage::Transfer {
+            from: owner,
+            to: other_user(meme_chain_id()),
+            amount,
+        })
+        .await;
+        let holders = meme
+            .state
+            .holders(usize::MAX, None, Amount::ZERO)
+            .await
+            .unwrap();
+        let mut pages = Vec::new();
+        let mut after = None;
+        loop {
+            let page = meme.state.holders(1, after, Amount::ZERO).await.unwrap();
+            let Some((_, cursor)) = page.last() else {
+                break;
+            };
+            after = Some(HolderCursor::decode(&cursor.encode()).unwrap());
+            pages.extend(page);
+        }
+        assert_eq!(pages, holders);
+        meme.execute_message(MemeMessage::Transfer {
+            from: other_user(meme_chain_id()),
+            to: owner,
+            amount,
+        })
+        .await;
+
+        let holders = meme
+            .state
+            .holders(usize::MAX, None, amount.try_add(Amount::ONE).unwrap())
+            .await
+            .unwrap();
+        assert!(holders.iter().all(|(holder, _)| holder.account != to));
+
+        // Account leaves holders when its balance is zero
+        meme.execute_message(MemeMessage::Transfer {
+            from: to,
+            to: owner,
+            amount,
+        })
+        .await;
+        assert_eq!(meme.state.holder_count(), holder_count);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_snapshot() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.state.owner().await;
+        let from = meme.owner_account();
+        let to = team_member(meme_chain_id());
+        let balance = meme.state.balance_of(from).await;
+        let total_supply = meme.state.meme().total_supply;
+
+        meme.execute_message(MemeMessage::Snapshot { owner }).await;
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to,
+            amount: Amount::from_tokens(10),
+        })
+        .await;
+        meme.execute_message(MemeMessage::Burn {
+            owner: from,
+            amount: Amount::from_tokens(5),
+        })
+        .await;
+
+        meme.execute_message(MemeMessage::Snapshot { owner }).await;
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to,
+            amount: Amount::from_tokens(1),
+        })
+        .await;
+
+        assert_eq!(meme.state.balance_of_at(from, 1).await.unwrap(), balance);
+        assert_eq!(
+            meme.state.balance_of_at(from, 2).await.unwrap(),
+            balance.try_sub(Amount::from_tokens(15)).unwrap()
+        );
+        assert_eq!(meme.state.balance_of_at(to, 1).await.unwrap(), Amount::ZERO);
+        assert_eq!(
+            meme.state.balance_of_at(to, 2).await.unwrap(),
+            Amount::from_tokens(10)
+        );
+        assert_eq!(meme.state.total_supply_at(1).unwrap(), total_supply);
+        assert_eq!(
+            meme.state.total_supply_at(2).unwrap(),
+            total_supply.try_sub(Amount::from_tokens(5)).unwrap()
+        );
+        assert!(meme.state.balance_of_at(from, 3).await.is_err());
+        assert!(meme.state.total_supply_at(0).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_update_metadata() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.state.owner().await;
+        let mut metadata = meme.state.meme().metadata;
+        metadata.telegram = Some("https://t.me/linera_meme".to_string());
+        let logo = CryptoHash::new(&TestString::new("New logo".to_string()));
+        metadata.logo = Some(logo);
+
+        let blob_gateway_application_id = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bb0",
+        )
+        .unwrap();
+        let ams_application_id = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bb1",
+        )
+        .unwrap();
+        meme.state
+            .blob_gateway_application_id
+            .set(Some(blob_gateway_application_id));
+        meme.state.ams_application_id.set(Some(ams_application_id));
+
+        // Blob gateway and AMS both respond Ok with the same encoding
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded_calls = calls.clone();
+        meme.runtime.set_call_application_handler(
+            move |_authenticated, application_id, operation| {
+                recorded_calls
+                    .lock()
+                    .unwrap()
+                    .push((application_id, operation));
+                bcs::to_bytes(&AmsResponse::Ok).unwrap()
+            },
+        );
+
+        meme.execute_message(MemeMessage::UpdateMetadata {
+            owner,
+            metadata: metadata.clone(),
+        })
+        .await;
+
+        assert_eq!(meme.state.meme().metadata, metadata);
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+
+        assert_eq!(calls[0].0, blob_gateway_application_id);
+        let BlobGatewayOperation::Register {
+            store_type,
+            data_type,
+            blob_hash,
+        } = bcs::from_bytes(&calls[0].1).unwrap();
+        assert_eq!(store_type, metadata.logo_store_type);
+        assert_eq!(data_type, BlobDataType::Image);
+        assert_eq!(blob_hash, logo);
+
+        assert_eq!(calls[1].0, ams_application_id);
+        let AmsOperation::Update {
+            application_id,
+            metadata: ams_metadata,
+        } = bcs::from_bytes(&calls[1].1).unwrap()
+        else {
+            panic!("Invalid AMS operation");
         };
+        assert_eq!(application_id, meme_application_id());
+        assert_eq!(ams_metadata.application_id, meme_application_id());
+        assert_eq!(ams_metadata.logo, logo);
+        assert_eq!(ams_metadata.telegram, metadata.telegram);
+    }
 
-        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
-        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
-        assert_eq!(balance, amount);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_update_metadata_same_logo() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.state.owner().await;
+        let mut metadata = meme.state.meme().metadata;
+        metadata.description = "Updated description".to_string();
+
+        let blob_gateway_application_id = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bb0",
+        )
+        .unwrap();
+        let ams_application_id = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bb1",
+        )
+        .unwrap();
+        meme.state
+            .blob_gateway_application_id
+            .set(Some(blob_gateway_application_id));
+        meme.state.ams_application_id.set(Some(ams_application_id));
+
+        // Unchanged logo is not registered to blob gateway again
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded_calls = calls.clone();
+        meme.runtime.set_call_application_handler(
+            move |_authenticated, application_id, _operation| {
+                recorded_calls.lock().unwrap().push(application_id);
+                bcs::to_bytes(&AmsResponse::Ok).unwrap()
+            },
+        );
+
+        meme.execute_message(MemeMessage::UpdateMetadata { owner, metadata })
+            .await;
+
+        assert_eq!(*calls.lock().unwrap(), vec![ams_application_id]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_update_metadata_invalid_owner() {
+        let mut meme = create_and_instantiate_meme().await;
+        let metadata = meme.state.meme().metadata;
+
+        meme.execute_message(MemeMessage::UpdateMetadata {
+            owner: team_member(meme_chain_id()),
+            metadata,
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InvalidOwner.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_update_metadata_invalid_link() {
+        let mut meme = create_and_instantiate_meme().await;
+        let owner = meme.state.owner().await;
+        let mut metadata = meme.state.meme().metadata;
+        metadata.telegram = Some("t.me/ broken".to_string());
+
+        meme.execute_message(MemeMessage::UpdateMetadata { owner, metadata })
+            .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InvalidMetadata.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_snapshot_invalid_owner() {
+        let mut meme = create_and_instantiate_meme().await;
+
+        meme.execute_message(MemeMessage::Snapshot {
+            owner: team_member(meme_chain_id()),
+        })
+        .await;
+
+        assert_eq!(
+            replied_failure(&meme),
+            Some(MemeError::InvalidOwner.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_transfer_tax_burn() {
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            transfer_tax: Some(TransferTax {
+                buy_bps: 0,
+                sell_bps: 0,
+                transfer_bps: 100,
+                destination: TaxDestination::Burn,
+                treasury: None,
+            }),
+            ..Default::default()
+        })
+        .await;
+        let from = meme.owner_account();
+        let to = team_member(meme_chain_id());
+        let to_balance = meme.state.balance_of(to).await;
+        let total_supply = meme.state.meme().total_supply;
+        let burned = meme.state.burned();
 
         meme.execute_message(MemeMessage::Transfer {
             from,
             to,
-            amount: transfer_amount,
+            amount: Amount::from_tokens(50),
         })
         .await;
 
-        assert_eq!(meme.state.balances.contains_key(&to).await.unwrap(), true);
-        let balance = meme.state.balances.get(&to).await.unwrap().unwrap();
-        assert_eq!(balance, amount);
+        assert_eq!(replied_failure(&meme), None);
+        assert_eq!(
+            meme.state.balance_of(to).await,
+            to_balance
+                .try_add(Amount::from_str("49.5").unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            meme.state.meme().total_supply,
+            total_supply
+                .try_sub(Amount::from_str("0.5").unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            meme.state.burned(),
+            burned.try_add(Amount::from_str("0.5").unwrap()).unwrap()
+        );
+        assert!(meme.events.contains(&MemeEvent::Tax {
+            from,
+            to,
+            amount: Amount::from_str("0.5").unwrap(),
+            destination: TaxDestination::Burn,
+        }));
+        assert!(meme.events.contains(&MemeEvent::Transfer {
+            from,
+            to,
+            amount: Amount::from_str("49.5").unwrap(),
+        }));
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn message_approve_owner_success() {
-        let mut meme = create_and_instantiate_meme().await;
-        let from = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: meme.runtime.authenticated_signer().unwrap(),
-        };
-
-        let amount = meme.state.initial_owner_balance().await;
-        let allowance = Amount::from_tokens(22);
-
-        let spender = Account {
-            chain_id: meme.runtime.chain_id(),
+    async fn message_transfer_tax_pool_treasury() {
+        let chain_id = meme_chain_id();
+        let treasury = Account {
+            chain_id,
             owner: AccountOwner::from_str(
-                "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc8f",
+                "0x7279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc7f",
             )
             .unwrap(),
         };
-
-        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
-        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
-        assert_eq!(balance, amount);
-
-        meme.execute_message(MemeMessage::Approve {
-            owner: from,
-            spender,
-            amount: allowance,
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            transfer_tax: Some(TransferTax {
+                buy_bps: 200,
+                sell_bps: 500,
+                transfer_bps: 0,
+                destination: TaxDestination::Treasury,
+                treasury: Some(treasury),
+            }),
+            ..Default::default()
         })
         .await;
+        let pool_application_id = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bae",
+        )
+        .unwrap();
+        meme.state
+            .set_pool_application(AccountOwner::from(pool_application_id));
+        let pool = Account {
+            chain_id,
+            owner: AccountOwner::from(pool_application_id),
+        };
+        let holder = meme.owner_account();
+        let to = team_member(chain_id);
+        let to_balance = meme.state.balance_of(to).await;
 
-        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
-        assert_eq!(balance, amount.try_sub(allowance).unwrap());
+        // Sell to pool
+        meme.execute_message(MemeMessage::Transfer {
+            from: holder,
+            to: pool,
+            amount: Amount::from_tokens(60),
+        })
+        .await;
+        assert_eq!(meme.state.balance_of(pool).await, Amount::from_tokens(57));
+        assert_eq!(
+            meme.state.balance_of(treasury).await,
+            Amount::from_tokens(3)
+        );
 
+        // Buy from pool
+        meme.execute_message(MemeMessage::TransferFromApplication {
+            caller: pool,
+            to,
+            amount: Amount::from_tokens(50),
+        })
+        .await;
+        assert_eq!(meme.state.balance_of(pool).await, Amount::from_tokens(7));
         assert_eq!(
-            meme.state.allowances.contains_key(&from).await.unwrap(),
-            true
+            meme.state.balance_of(to).await,
+            to_balance.try_add(Amount::from_tokens(49)).unwrap()
         );
+        assert!(meme.events.contains(&MemeEvent::Tax {
+            from: pool,
+            to,
+            amount: Amount::ONE,
+            destination: TaxDestination::Treasury,
+        }));
+        assert!(meme.events.contains(&MemeEvent::Transfer {
+            from: pool,
+            to,
+            amount: Amount::from_tokens(49),
+        }));
         assert_eq!(
-            meme.state
-                .allowances
-                .get(&from)
-                .await
-                .unwrap()
-                .unwrap()
-                .contains_key(&spender),
-            true
+            meme.state.balance_of(treasury).await,
+            Amount::from_tokens(4)
         );
-        let balance = *meme
-            .state
-            .allowances
-            .get(&from)
-            .await
-            .unwrap()
-            .unwrap()
-            .get(&spender)
-            .unwrap();
-        assert_eq!(balance, allowance);
 
-        meme.execute_message(MemeMessage::Approve {
-            owner: from,
-            spender,
-            amount: allowance,
+        // Transfer between holders is not taxed
+        meme.execute_message(MemeMessage::Transfer {
+            from: holder,
+            to,
+            amount: Amount::from_tokens(10),
         })
         .await;
-
-        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
         assert_eq!(
-            balance,
-            amount
-                .try_sub(allowance)
-                .unwrap()
-                .try_sub(allowance)
-                .unwrap()
+            meme.state.balance_of(to).await,
+            to_balance.try_add(Amount::from_tokens(59)).unwrap()
         );
+        assert_eq!(
+            meme.state.balance_of(treasury).await,
+            Amount::from_tokens(4)
+        );
+    }
 
-        let balance = *meme
-            .state
-            .allowances
-            .get(&from)
-            .await
-            .unwrap()
-            .unwrap()
-            .get(&spender)
-            .unwrap();
-        assert_eq!(balance, allowance.try_mul(2).unwrap());
-
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_transfer_tax_reflection() {
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            transfer_tax: Some(TransferTax {
+                buy_bps: 0,
+                sell_bps: 0,
+                transfer_bps: 1000,
+                destination: TaxDestination::Reflection,
+                treasury: None,
+            }),
+            ..Default::default()
+        })
+        .await;
+        let from = meme.owner_account();
         let to = Account {
             chain_id: meme.runtime.chain_id(),
             owner: AccountOwner::from_str(
-                "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e08",
+                "0x7279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc7f",
             )
             .unwrap(),
         };
+        let balance = meme.state.balance_of(from).await;
+        let total_supply = meme.state.meme().total_supply;
 
-        meme.execute_message(MemeMessage::TransferFrom {
-            owner: spender,
+        meme.execute_message(MemeMessage::Transfer {
             from,
             to,
-            amount: allowance,
+            amount: Amount::from_tokens(50),
         })
         .await;
 
-        let balance = *meme
+        // Tax is reflected to the remaining balance of sender, receiver holds nothing before
+        let remaining = balance.try_sub(Amount::from_tokens(50)).unwrap();
+        assert_eq!(meme.state.balance_of(to).await, Amount::from_tokens(45));
+        assert_eq!(
+            meme.state.balance_of(from).await,
+            remaining.try_add(Amount::from_tokens(5)).unwrap()
+        );
+        assert_eq!(meme.state.meme().total_supply, total_supply);
+        assert!(meme.events.contains(&MemeEvent::Tax {
+            from,
+            to,
+            amount: Amount::from_tokens(5),
+            destination: TaxDestination::Reflection,
+        }));
+
+        // Holders show settled balance, cursor keeps the indexed one
+        let holders = meme
             .state
-            .allowances
-            .get(&from)
+            .holders(usize::MAX, None, Amount::ZERO)
             .await
-            .unwrap()
-            .unwrap()
-            .get(&spender)
             .unwrap();
-        assert_eq!(balance, allowance);
+        let (holder, cursor) = holders
+            .iter()
+            .find(|(holder, _)| holder.account == from)
+            .unwrap();
+        assert_eq!(
+            holder.balance,
+            remaining.try_add(Amount::from_tokens(5)).unwrap()
+        );
+        assert_eq!(cursor.balance, remaining);
 
-        let balance = meme.state.balances.get(&to).await.unwrap().unwrap();
-        assert_eq!(balance, allowance);
+        // Reflection is settled into balance at the next balance change
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to: team_member(meme_chain_id()),
+            amount: remaining.try_add(Amount::from_tokens(5)).unwrap(),
+        })
+        .await;
+        assert_eq!(replied_failure(&meme), None);
+        assert_eq!(meme.state.balance_of(from).await, Amount::ZERO);
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    #[should_panic(expected = "Insufficient balance")]
-    async fn message_approve_insufficient_balance() {
-        let mut meme = create_and_instantiate_meme().await;
-        let from = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: meme.runtime.authenticated_signer().unwrap(),
-        };
-
-        let amount = meme.state.initial_owner_balance().await;
-        let allowance = Amount::from_tokens(220);
+    async fn snapshot_excludes_later_reflection() {
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            transfer_tax: Some(TransferTax {
+                buy_bps: 0,
+                sell_bps: 0,
+                transfer_bps: 1000,
+                destination: TaxDestination::Reflection,
+                treasury: None,
+            }),
+            ..Default::default()
+        })
+        .await;
+        let from = meme.owner_account();
+        let to = other_user(meme_chain_id());
+        let owner = meme.state.owner().await;
 
-        let spender = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: AccountOwner::from_str(
-                "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc8f",
-            )
-            .unwrap(),
-        };
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to,
+            amount: Amount::from_tokens(50),
+        })
+        .await;
+        let balance = meme.state.balance_of(from).await;
 
-        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
-        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
-        assert_eq!(balance, amount);
+        meme.execute_message(MemeMessage::Snapshot { owner }).await;
+        let snapshot_id = *meme.state.snapshot_id.get();
 
-        // It won't panic here, it'll approved from application balance
-        meme.execute_message(MemeMessage::Approve {
-            owner: from,
-            spender,
-            amount: allowance,
+        // Reflection of transfer after snapshot accrues to from without changing its balance
+        meme.execute_message(MemeMessage::Transfer {
+            from: to,
+            to: team_member(meme_chain_id()),
+            amount: Amount::from_tokens(10),
         })
         .await;
+        assert!(meme.state.balance_of(from).await > balance);
+        assert_eq!(
+            meme.state.balance_of_at(from, snapshot_id).await.unwrap(),
+            balance
+        );
 
-        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
-        assert_eq!(balance, amount);
-
+        // Checkpoint written at the next balance change doesn't count it either
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to,
+            amount: Amount::ONE,
+        })
+        .await;
         assert_eq!(
-            meme.state.allowances.contains_key(&from).await.unwrap(),
-            false
+            meme.state.balance_of_at(from, snapshot_id).await.unwrap(),
+            balance
         );
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    #[should_panic(expected = "Insufficient balance")]
-    async fn message_approve_meme_owner_self_insufficient_balance() {
-        let mut meme = create_and_instantiate_meme().await;
-        let from = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: meme.runtime.authenticated_signer().unwrap(),
-        };
-
-        let amount = meme.state.initial_owner_balance().await;
-        let allowance = Amount::from_tokens(220);
-
-        assert_eq!(meme.state.balances.contains_key(&from).await.unwrap(), true);
-        let balance = meme.state.balances.get(&from).await.unwrap().unwrap();
-        assert_eq!(balance, amount);
+    async fn snapshot_then_reflected_transfers() {
+        let mut meme = create_and_instantiate_meme_with(MemeOptions {
+            transfer_tax: Some(TransferTax {
+                buy_bps: 0,
+                sell_bps: 0,
+                transfer_bps: 1000,
+                destination: TaxDestination::Reflection,
+                treasury: None,
+            }),
+            ..Default::default()
+        })
+        .await;
+        let from = meme.owner_account();
+        let to = other_user(meme_chain_id());
+        let owner = meme.state.owner().await;
 
-        // It won't panic here, it'll approved from application balance
-        meme.execute_message(MemeMessage::Approve {
-            owner: from,
-            spender: from,
-            amount: allowance,
+        meme.execute_message(MemeMessage::Transfer {
+            from,
+            to,
+            amount: Amount::from_tokens(50),
         })
         .await;
+        let from_balance = meme.state.balance_of(from).await;
+        let to_balance = meme.state.balance_of(to).await;
+
+        meme.execute_message(MemeMessage::Snapshot { owner }).await;
+        let snapshot_id = *meme.state.snapshot_id.get();
+
+        // Reflection debts are raised above snapshot accumulator by these transfers, later
+        // balance changes of the same holders must still be checkpointed
+        for (sender, receiver) in [(from, to), (to, from), (from, to), (to, from)] {
+            meme.execute_message(MemeMessage::Transfer {
+                from: sender,
+                to: receiver,
+                amount: Amount::from_tokens(10),
+            })
+            .await;
+            assert!(matches!(
+                meme.runtime
+                    .created_send_message_requests()
+                    .last()
+                    .unwrap()
+                    .message,
+                MemeMessage::OperationSucceeded { owner } if owner == sender
+            ));
+        }
 
         assert_eq!(
-            meme.state.allowances.contains_key(&from).await.unwrap(),
-            false
+            meme.state.balance_of_at(from, snapshot_id).await.unwrap(),
+            from_balance
+        );
+        assert_eq!(
+            meme.state.balance_of_at(to, snapshot_id).await.unwrap(),
+            to_balance
         );
     }
 
-    #[tokio::test(flavor = "multi_thread")]
-    async fn message_transfer_ownership() {
-        let mut meme = create_and_instantiate_meme().await;
-        let owner = Account {
-            chain_id: meme.runtime.chain_id(),
-            owner: meme.runtime.authenticated_signer().unwrap(),
-        };
-        let new_owner = Account {
-            chain_id: meme.runtime.chain_id(),
+    fn replied_failure(meme: &MemeContract) -> Option<String> {
+        match &meme.runtime.created_send_message_requests().last()?.message {
+            MemeMessage::OperationFailed { reason, .. } => Some(reason.clone()),
+            _ => None,
+        }
+    }
+
+    fn meme_application_id() -> ApplicationId {
+        ApplicationId::from_str("b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bad")
+            .unwrap()
+    }
+
+    fn meme_chain_id() -> ChainId {
+        ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+            .unwrap()
+    }
+
+    // Signer and creator of the test meme
+    fn operator() -> AccountOwner {
+        AccountOwner::from_str("0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc7f")
+            .unwrap()
+    }
+
+    fn team_member(chain_id: ChainId) -> Account {
+        Account {
+            chain_id,
             owner: AccountOwner::from_str(
-                "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc8f",
+                "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e00",
             )
             .unwrap(),
-        };
-
-        // It won't panic here, it'll approved from application balance
-        meme.execute_message(MemeMessage::TransferOwnership { owner, new_owner })
-            .await;
+        }
+    }
 
-        assert_eq!(meme.state.owner.get().unwrap(), new_owner);
+    fn other_user(chain_id: ChainId) -> Account {
+        Account {
+            chain_id,
+            owner: AccountOwner::from_str(
+                "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc8f",
+            )
+            .unwrap(),
+        }
     }
 
-    #[test]
-    fn cross_application_call() {}
+    fn owner_balance() -> Amount {
+        Amount::from_tokens(100)
+    }
 
-    fn mock_application_call(
-        _authenticated: bool,
-        _application_id: ApplicationId,
-        _operation: Vec<u8>,
-    ) -> Vec<u8> {
-        bcs::to_bytes(&SwapResponse::ChainId(
-            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
-                .unwrap(),
-        ))
-        .unwrap()
+    // Optional configurations of the test meme, the others are shared by all tests
+    #[derive(Default)]
+    struct MemeOptions {
+        reward_split: Option<RewardSplit>,
+        presale: Option<PresaleConfig>,
+        genesis_miner_airdrop: Option<GenesisMinerAirdrop>,
+        transfer_tax: Option<TransferTax>,
+        // Genesis miners are only trusted when meme is created by proxy
+        without_proxy: bool,
     }
 
     async fn create_and_instantiate_meme() -> MemeContract {
-        let operator = AccountOwner::from_str(
-            "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc7f",
-        )
-        .unwrap();
-        let chain_id =
-            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
-                .unwrap();
+        create_and_instantiate_meme_with(MemeOptions::default()).await
+    }
+
+    async fn create_and_instantiate_meme_with(options: MemeOptions) -> MemeContract {
+        let MemeOptions {
+            reward_split,
+            presale,
+            genesis_miner_airdrop,
+            transfer_tax,
+            without_proxy,
+        } = options;
+        let operator = operator();
+        let chain_id = meme_chain_id();
         let owner = Account {
             chain_id,
             owner: operator,
         };
 
-        let application_id = ApplicationId::from_str(
-            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bad",
-        )
-        .unwrap()
-        .with_abi::<MemeAbi>();
+        let application_id = meme_application_id().with_abi::<MemeAbi>();
         let application = Account {
             chain_id,
             owner: AccountOwner::from(application_id.forget_abi()),
@@ -971,6 +4735,10 @@ mod tests {
             chain_id,
             owner: AccountOwner::from(swap_application_id),
         };
+        let proxy_application_id = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5baf",
+        )
+        .unwrap();
 
         let initial_supply = Amount::from_tokens(21000000);
         let swap_allowance = Amount::from_tokens(10000000);
@@ -980,8 +4748,11 @@ mod tests {
                 fungible_amount: swap_allowance,
                 native_amount: Amount::from_tokens(10),
             }),
-            virtual_initial_liquidity: true,
+            // Presale funds could only be added to real liquidity
+            virtual_initial_liquidity: presale.is_none(),
             swap_creator_chain_id: chain_id,
+            pow_algorithm: HashAlgorithm::Keccak256,
+            leader_election: LeaderElectionMethod::MultiLeaderRoundRobin,
         };
         let runtime = ContractRuntime::new()
             .with_can_change_application_permissions(true)
@@ -999,12 +4770,16 @@ mod tests {
             .with_call_application_handler(mock_application_call)
             .with_application_creator_chain_id(chain_id)
             .with_application_parameters(parameters.clone())
-            .with_authenticated_signer(operator);
+            .with_authenticated_signer(operator)
+            .with_block_height(BlockHeight::from(1))
+            .with_system_time(0.into())
+            .with_message_is_bouncing(Some(false));
         let mut contract = MemeContract {
             state: MemeState::load(runtime.root_view_storage_context())
                 .blocking_wait()
                 .expect("Failed to read from mock key value store"),
             runtime,
+            events: Vec::new(),
         };
 
         let instantiation_argument = InstantiationArgument {
@@ -1025,20 +4800,84 @@ mod tests {
                     github: None,
                     live_stream: None,
                 },
-                virtual_initial_liquidity: true,
+                virtual_initial_liquidity: parameters.virtual_initial_liquidity,
                 initial_liquidity: parameters.initial_liquidity,
+                mining: MiningConfig {
+                    initial_difficulty: 1,
+                    target_block_time_ms: 5000,
+                    retarget_interval: 10,
+                    mining_pool: None,
+                    pow_algorithm: HashAlgorithm::Keccak256,
+                },
+                emission: Some(EmissionPolicy {
+                    initial_block_reward: Amount::from_tokens(50),
+                    decay: RewardDecay::Halving,
+                    decay_interval: 100,
+                    decay_rate_bps: 0,
+                    hard_cap: Amount::from_tokens(42000000),
+                }),
+                staking: Some(StakingConfig {
+                    unbonding_period_ms: 1000,
+                    min_stake: Amount::from_tokens(10),
+                }),
+                transfer_tax,
             },
             blob_gateway_application_id: None,
             ams_application_id: None,
-            proxy_application_id: None,
+            proxy_application_id: Some(proxy_application_id),
             swap_application_id: Some(swap_application_id),
+            reward_split,
+            creator_vesting: Some(VestingSchedule {
+                amount: Amount::from_tokens(1000),
+                cliff_ms: 1000,
+                duration_ms: 10000,
+                release: VestingRelease::Linear,
+                step_ms: 0,
+            }),
+            team_vesting: vec![TeamAllocation {
+                beneficiary: team_member(chain_id),
+                schedule: VestingSchedule {
+                    amount: Amount::from_tokens(400),
+                    cliff_ms: 0,
+                    duration_ms: 4000,
+                    release: VestingRelease::Step,
+                    step_ms: 1000,
+                },
+            }],
+            presale: presale.clone(),
+            genesis_miners: vec![owner, team_member(chain_id)],
+            genesis_miner_airdrop,
         };
 
+        // Proxy creates meme application, then other calls come from swap application
+        if !without_proxy {
+            contract
+                .runtime
+                .set_authenticated_caller_id(Some(proxy_application_id));
+        }
         contract.instantiate(instantiation_argument.clone()).await;
+        contract
+            .runtime
+            .set_authenticated_caller_id(Some(swap_application_id));
         let application_balance = initial_supply
             .try_sub(swap_allowance)
             .unwrap()
-            .try_sub(contract.state.initial_owner_balance().await)
+            .try_sub(Amount::from_tokens(1400))
+            .unwrap()
+            .try_sub(
+                presale
+                    .map(|presale| {
+                        presale
+                            .whitelist
+                            .iter()
+                            .fold(Amount::ZERO, |reserved, allocation| {
+                                reserved.try_add(allocation.cap).unwrap()
+                            })
+                    })
+                    .unwrap_or(Amount::ZERO),
+            )
+            .unwrap()
+            .try_sub(contract.state.airdropped())
             .unwrap();
 
         assert_eq!(
@@ -1086,7 +4925,7 @@ mod tests {
             true
         );
         assert_eq!(
-            *contract
+            contract
                 .state
                 .allowances
                 .get(&application)
@@ -1094,9 +4933,19 @@ mod tests {
                 .unwrap()
                 .unwrap()
                 .get(&swap_application)
-                .unwrap(),
+                .unwrap()
+                .amount,
             swap_allowance
         );
+        // Creator holds nothing unlocked except its genesis miner airdrop
+        assert!(contract.state.balance_of(owner).await <= contract.state.airdropped());
+
+        // Fund signer of tests from application
+        contract
+            .state
+            .transfer(application, owner, owner_balance())
+            .await
+            .unwrap();
 
         contract
     }
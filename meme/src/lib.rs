@@ -27,4 +27,82 @@ pub enum MemeError {
 
     #[error("Self transfer")]
     SelfTransfer,
+
+    #[error("Already mined")]
+    AlreadyMined,
+
+    #[error("Invalid nonce")]
+    InvalidNonce,
+
+    #[error("Not mining pool")]
+    NotMiningPool,
+
+    #[error("Duplicate share")]
+    DuplicateShare,
+
+    #[error("Invalid proposer")]
+    InvalidProposer,
+
+    #[error("Staking disabled")]
+    StakingDisabled,
+
+    #[error("Insufficient stake")]
+    InsufficientStake,
+
+    #[error("Insufficient allowance")]
+    InsufficientAllowance,
+
+    #[error("No vesting")]
+    NoVesting,
+
+    #[error("Allowance expired")]
+    AllowanceExpired,
+
+    #[error("Invalid expiry")]
+    InvalidExpiry,
+
+    #[error("Invalid metadata")]
+    InvalidMetadata,
+
+    #[error("Invalid snapshot")]
+    InvalidSnapshot,
+
+    #[error("No pending operation")]
+    NoPendingOperation,
+
+    #[error("Invalid batch size")]
+    InvalidBatchSize,
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Permit expired")]
+    PermitExpired,
+
+    #[error("Invalid permit nonce")]
+    InvalidPermitNonce,
+
+    #[error("Invalid permit token")]
+    InvalidPermitToken,
+
+    #[error("Presale disabled")]
+    PresaleDisabled,
+
+    #[error("Presale closed")]
+    PresaleClosed,
+
+    #[error("Presale not ended")]
+    PresaleNotEnded,
+
+    #[error("Presale not closed")]
+    PresaleNotClosed,
+
+    #[error("Presale locked")]
+    PresaleLocked,
+
+    #[error("Not whitelisted")]
+    NotWhitelisted,
+
+    #[error("Exceed allocation")]
+    ExceedAllocation,
 }
@@ -7,10 +7,20 @@ mod state;
 
 use std::{str::FromStr, sync::Arc};
 
-use abi::meme::{Meme, MemeAbi};
-use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Response, Schema};
+use abi::{
+    election::{LeaderElection, LeaderElectionMethod},
+    meme::{
+        Allowance, BatchTransferItem, Holder, HolderCursor, HolderPage, Meme, MemeAbi,
+        MemeOperation, MiningInfo, MiningShare, PendingOperation, Presale, RewardSplit, Snapshot,
+        Unbonding, Vesting, MAX_HOLDERS_PAGE,
+    },
+};
+use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
-    linera_base_types::{Account, Amount, ChainId, WithServiceAbi},
+    bcs,
+    linera_base_types::{
+        Account, AccountOwner, Amount, BlockHeight, ChainId, CryptoHash, WithServiceAbi,
+    },
     views::View,
     Service, ServiceRuntime,
 };
@@ -47,7 +57,7 @@ impl Service for MemeService {
                 state: self.state.clone(),
                 runtime: self.runtime.clone(),
             },
-            EmptyMutation,
+            MutationRoot,
             EmptySubscription,
         )
         .finish();
@@ -73,6 +83,26 @@ impl QueryRoot {
             .await
     }
 
+    // Latest snapshot id, 0 means no snapshot is taken
+    async fn snapshot_id(&self) -> u64 {
+        *self.state.snapshot_id.get()
+    }
+
+    async fn snapshot(&self, id: u64) -> Option<Snapshot> {
+        self.state.snapshot_of(id).await.unwrap()
+    }
+
+    async fn balance_of_at(&self, owner: String, snapshot_id: u64) -> Amount {
+        self.state
+            .balance_of_at(Account::from_str(&owner).unwrap(), snapshot_id)
+            .await
+            .unwrap()
+    }
+
+    async fn total_supply_at(&self, snapshot_id: u64) -> Amount {
+        self.state.total_supply_at(snapshot_id).unwrap()
+    }
+
     // async fn allowance_of(&self, owner: Account, spender: Account) -> Amount {
     async fn allowance_of(&self, owner: String, spender: String) -> Amount {
         self.state
@@ -83,8 +113,70 @@ impl QueryRoot {
             .await
     }
 
-    async fn initial_owner_balance(&self) -> Amount {
-        self.state.initial_owner_balance().await
+    // Holders ordered by balance descending, pass end cursor of previous page as after
+    async fn holders(
+        &self,
+        first: Option<usize>,
+        after: Option<String>,
+        min_balance: Option<Amount>,
+    ) -> async_graphql::Result<HolderPage> {
+        let first = first.unwrap_or(MAX_HOLDERS_PAGE).min(MAX_HOLDERS_PAGE);
+        let after = after
+            .map(|after| {
+                HolderCursor::decode(&after).ok_or_else(|| format!("Invalid cursor: {}", after))
+            })
+            .transpose()?;
+        let mut holders = self
+            .state
+            .holders(first + 1, after, min_balance.unwrap_or(Amount::ZERO))
+            .await
+            .unwrap();
+        let has_next_page = holders.len() > first;
+        holders.truncate(first);
+
+        Ok(HolderPage {
+            end_cursor: holders.last().map(|(_, cursor)| cursor.encode()),
+            holders: holders.into_iter().map(|(holder, _)| holder).collect(),
+            has_next_page,
+        })
+    }
+
+    async fn holder_count(&self) -> u64 {
+        self.state.holder_count()
+    }
+
+    async fn top_holders(&self, n: usize) -> Vec<Holder> {
+        self.state
+            .holders(n.min(MAX_HOLDERS_PAGE), None, Amount::ZERO)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(holder, _)| holder)
+            .collect()
+    }
+
+    // Every spender approved by owner with unspent escrow
+    async fn allowances(&self, owner: String) -> Vec<Allowance> {
+        self.state
+            .allowances_of(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
+    }
+
+    // Tracked transfers sent from this chain, only meaningful on the sender chain
+    async fn pending_operations(&self, owner: String) -> Vec<PendingOperation> {
+        self.state
+            .pending_operations_of(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
+    }
+
+    // Nonce should be signed in the next permit of owner
+    async fn permit_nonce(&self, owner: String) -> u64 {
+        self.state
+            .permit_nonce(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
     }
 
     async fn creator_chain_id(&self) -> ChainId {
@@ -94,6 +186,170 @@ impl QueryRoot {
     async fn meme(&self) -> Meme {
         self.state.meme.get().as_ref().unwrap().clone()
     }
+
+    async fn mining_info(&self) -> MiningInfo {
+        self.state.mining_info()
+    }
+
+    // Height of the block which will contain the next mine operation on this chain
+    async fn next_block_height(&self) -> BlockHeight {
+        self.runtime.next_block_height()
+    }
+
+    async fn difficulty(&self) -> u64 {
+        self.state.difficulty()
+    }
+
+    async fn target_block_time(&self) -> u64 {
+        self.state.target_block_time_ms()
+    }
+
+    async fn current_block_reward(&self) -> Amount {
+        self.state.current_block_reward()
+    }
+
+    async fn mined_supply(&self) -> Amount {
+        self.state.mined_supply()
+    }
+
+    async fn remaining_supply(&self) -> Amount {
+        self.state.remaining_supply()
+    }
+
+    async fn burned(&self) -> Amount {
+        self.state.burned()
+    }
+
+    async fn reward_split(&self) -> Option<RewardSplit> {
+        self.state.reward_split()
+    }
+
+    async fn pool_application(&self) -> Option<AccountOwner> {
+        self.state.pool_application()
+    }
+
+    async fn share_difficulty(&self) -> Option<u64> {
+        self.state.share_difficulty()
+    }
+
+    async fn pplns_shares(&self) -> Vec<MiningShare> {
+        self.state.pplns_shares().await.unwrap()
+    }
+
+    async fn miner_beneficiary(&self, miner: Account) -> Option<Account> {
+        self.state.miner_beneficiary(miner).await.unwrap()
+    }
+
+    async fn election_method(&self) -> LeaderElectionMethod {
+        self.state.election_method()
+    }
+
+    async fn leader_election(&self) -> Option<LeaderElection> {
+        self.state.leader_election()
+    }
+
+    // Owner who is allowed to propose block at (height, round) with current chain owners
+    async fn leader_for(&self, height: BlockHeight, round: u32) -> Option<AccountOwner> {
+        self.state.leader_for(height, round).await.unwrap()
+    }
+
+    async fn total_staked(&self) -> Amount {
+        self.state.total_staked()
+    }
+
+    async fn stake_of(&self, owner: String) -> Amount {
+        self.state
+            .stake_of(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn unbondings_of(&self, owner: String) -> Vec<Unbonding> {
+        self.state
+            .unbondings_of(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn vesting_of(&self, owner: String) -> Option<Vesting> {
+        self.state
+            .vesting_of(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn presale(&self) -> Option<Presale> {
+        self.state.presale()
+    }
+
+    async fn presale_cap_of(&self, owner: String) -> Amount {
+        self.state
+            .presale_cap_of(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
+    }
+
+    // Purchased presale tokens which are not claimed yet
+    async fn presale_purchase_of(&self, owner: String) -> Amount {
+        self.state
+            .presale_purchase_of(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn airdropped(&self) -> Amount {
+        self.state.airdropped()
+    }
+
+    async fn airdrop_vesting_of(&self, owner: String) -> Option<Vesting> {
+        self.state
+            .airdrop_vesting_of(Account::from_str(&owner).unwrap())
+            .await
+            .unwrap()
+    }
+
+    // Vested amount of allocation and airdrop which could be claimed at the current time
+    async fn vesting_claimable(&self, owner: String) -> Amount {
+        self.state
+            .vesting_claimable(
+                Account::from_str(&owner).unwrap(),
+                self.runtime.system_time(),
+            )
+            .await
+            .unwrap()
+    }
+}
+
+struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn mine(&self, nonce: CryptoHash) -> Vec<u8> {
+        bcs::to_bytes(&MemeOperation::Mine { nonce }).unwrap()
+    }
+
+    async fn submit_share(&self, nonce: CryptoHash) -> Vec<u8> {
+        bcs::to_bytes(&MemeOperation::SubmitShare { nonce }).unwrap()
+    }
+
+    // One transfer per line as `account,amount`, empty lines are ignored
+    async fn batch_transfer_csv(&self, csv: String) -> async_graphql::Result<Vec<u8>> {
+        let transfers = csv
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (to, amount) = line
+                    .split_once(',')
+                    .ok_or_else(|| format!("Invalid transfer: {}", line))?;
+                Ok(BatchTransferItem {
+                    to: Account::from_str(to.trim())?,
+                    amount: Amount::from_str(amount.trim())?,
+                })
+            })
+            .collect::<async_graphql::Result<Vec<_>>>()?;
+        Ok(bcs::to_bytes(&MemeOperation::BatchTransfer { transfers })?)
+    }
 }
 
 #[cfg(test)]
@@ -101,12 +357,16 @@ mod tests {
     use std::sync::Arc;
 
     use abi::{
-        meme::{InstantiationArgument, Meme, Metadata},
+        meme::{
+            BatchTransferItem, HashAlgorithm, InstantiationArgument, Meme, MemeOperation, Metadata,
+            MiningConfig,
+        },
         store_type::StoreType,
     };
     use async_graphql::{Request, Response, Value};
     use futures::FutureExt as _;
     use linera_sdk::{
+        bcs,
         linera_base_types::{
             Account, AccountOwner, Amount, ApplicationId, ChainId, CryptoHash, TestString,
         },
@@ -150,11 +410,27 @@ mod tests {
                 },
                 virtual_initial_liquidity: true,
                 initial_liquidity: None,
+                mining: MiningConfig {
+                    initial_difficulty: 1,
+                    target_block_time_ms: 5000,
+                    retarget_interval: 10,
+                    mining_pool: None,
+                    pow_algorithm: HashAlgorithm::Keccak256,
+                },
+                emission: None,
+                staking: None,
+                transfer_tax: None,
             },
             blob_gateway_application_id: None,
             ams_application_id: None,
             proxy_application_id: None,
             swap_application_id: Some(application_id),
+            reward_split: None,
+            creator_vesting: None,
+            team_vesting: vec![],
+            presale: None,
+            genesis_miners: vec![],
+            genesis_miner_airdrop: None,
         };
 
         let chain_id =
@@ -198,4 +474,115 @@ mod tests {
 
         assert_eq!(response, expected)
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn query_holders_invalid_cursor() {
+        let runtime = Arc::new(ServiceRuntime::<MemeService>::new());
+        let state = MemeState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+        let service = MemeService {
+            state: Arc::new(state),
+            runtime,
+        };
+        let request = Request::new("{ holders(after: \"invalid\") { hasNextPage } }");
+
+        let response = service
+            .handle_query(request)
+            .now_or_never()
+            .expect("Query should not await anything");
+
+        assert_eq!(response.errors.len(), 1);
+        assert_eq!(response.errors[0].message, "Invalid cursor: invalid");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mutation() {
+        let runtime = Arc::new(ServiceRuntime::<MemeService>::new());
+        let state = MemeState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+        let service = MemeService {
+            state: Arc::new(state),
+            runtime,
+        };
+
+        let nonce = CryptoHash::new(&TestString::new("nonce".to_string()));
+        let request = Request::new(format!("mutation {{ mine(nonce: \"{}\") }}", nonce));
+
+        let response = service
+            .handle_query(request)
+            .now_or_never()
+            .expect("Query should not await anything");
+
+        let expected = Response::new(
+            Value::from_json(json!({
+                "mine": bcs::to_bytes(&MemeOperation::Mine { nonce }).unwrap()
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(response, expected)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mutation_batch_transfer_csv() {
+        let runtime = Arc::new(ServiceRuntime::<MemeService>::new());
+        let state = MemeState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+        let service = MemeService {
+            state: Arc::new(state),
+            runtime,
+        };
+
+        let chain_id =
+            ChainId::from_str("899dd894c41297e9dd1221fa02845efc81ed8abd9a0b7d203ad514b3aa6b2d46")
+                .unwrap();
+        let to_1 = Account {
+            chain_id,
+            owner: AccountOwner::from_str(
+                "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e00",
+            )
+            .unwrap(),
+        };
+        let to_2 = Account {
+            chain_id,
+            owner: AccountOwner::from_str(
+                "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc7f",
+            )
+            .unwrap(),
+        };
+        let csv = format!("{},1.5\n\n{}, 20\n", to_1, to_2);
+        let request = Request::new(format!(
+            "mutation {{ batchTransferCsv(csv: {}) }}",
+            serde_json::to_string(&csv).unwrap()
+        ));
+
+        let response = service
+            .handle_query(request)
+            .now_or_never()
+            .expect("Query should not await anything");
+
+        let expected = Response::new(
+            Value::from_json(json!({
+                "batchTransferCsv": bcs::to_bytes(&MemeOperation::BatchTransfer {
+                    transfers: vec![
+                        BatchTransferItem {
+                            to: to_1,
+                            amount: Amount::from_str("1.5").unwrap(),
+                        },
+                        BatchTransferItem {
+                            to: to_2,
+                            amount: Amount::from_tokens(20),
+                        },
+                    ],
+                })
+                .unwrap()
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(response, expected)
+    }
 }
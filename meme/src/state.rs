@@ -2,22 +2,39 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use abi::{
-    meme::{InstantiationArgument, Liquidity, Meme},
+    election::{
+        election_round, election_seed, round_robin_leader, stake_weighted_leader, LeaderElection,
+        LeaderElectionMethod,
+    },
+    meme::{
+        AirdropSplit, Allowance, BatchTransferItem, Checkpoint, GenesisMinerAirdrop, Holder,
+        HolderCursor, InstantiationArgument, Liquidity, Meme, Metadata, MiningInfo, MiningPool,
+        MiningShare, PendingOperation, PendingOperationKind, PendingOperationStatus, Permit,
+        Presale, PresaleConfig, RewardShares, RewardSplit, Snapshot, StakingConfig, TaxDestination,
+        TeamAllocation, TransferTax, Unbonding, Vesting, VestingSchedule, MAX_BATCH_TRANSFERS,
+        MAX_PENDING_OPERATIONS,
+    },
+    pow::{genesis_hash, retarget_difficulty, MiningChallenge},
     store_type::StoreType,
 };
 use linera_sdk::{
-    ensure,
-    linera_base_types::{Account, AccountOwner, Amount, ApplicationId, ChainId, CryptoHash},
-    views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext},
+    bcs, ensure,
+    linera_base_types::{
+        Account, AccountOwner, Amount, ApplicationId, ArithmeticError, BlockHeight, ChainId,
+        CryptoHash, TimeDelta, TimeoutConfig, Timestamp,
+    },
+    views::{
+        linera_views::{self, map_view::ByteMapView},
+        MapView, RegisterView, RootView, ViewError, ViewStorageContext,
+    },
 };
 use meme::MemeError;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// The application state.
 #[derive(RootView)]
 #[view(context = "ViewStorageContext")]
 pub struct MemeState {
-    pub initial_owner_balance: RegisterView<Amount>,
     pub owner: RegisterView<Option<Account>>,
     pub holder: RegisterView<Option<Account>>,
 
@@ -29,12 +46,94 @@ pub struct MemeState {
     pub ams_application_id: RegisterView<Option<ApplicationId>>,
     pub proxy_application_id: RegisterView<Option<ApplicationId>>,
     pub swap_application_id: RegisterView<Option<ApplicationId>>,
+    // Meme native pool initialized by swap application, its transfers are taxed as buy and sell
+    pub pool_application: RegisterView<Option<AccountOwner>>,
 
     // Account information
     pub balances: MapView<Account, Amount>,
-    pub allowances: MapView<Account, HashMap<Account, Amount>>,
+    // Accounts with positive balance, keys are ordered by balance descending
+    pub holders: ByteMapView<()>,
+    pub holder_count: RegisterView<u64>,
+
+    // Reflected transfer tax is settled into balance at the next balance change of each holder
+    pub reflection_per_token: RegisterView<u128>,
+    pub reflection_supply: RegisterView<Amount>,
+    pub reflection_debts: MapView<Account, u128>,
+
+    // Balances and total supply are checkpointed lazily at the first write after snapshot
+    pub snapshot_id: RegisterView<u64>,
+    pub snapshots: MapView<u64, Snapshot>,
+    // Reflection per token when each snapshot is taken, later reflections are not in the snapshot
+    pub snapshot_reflections: MapView<u64, u128>,
+    pub balance_checkpoints: MapView<Account, Vec<Checkpoint>>,
+    pub total_supply_checkpoints: RegisterView<Vec<Checkpoint>>,
+    pub allowances: MapView<Account, HashMap<Account, Allowance>>,
+    // Tracked transfers sent from this chain, resolved by replies from creation chain
+    pub pending_operation_id: RegisterView<u64>,
+    pub pending_operations: MapView<Account, Vec<PendingOperation>>,
+    // Next nonce of signed permit, each permit could only be applied once
+    pub permit_nonces: MapView<Account, u64>,
+
+    // Mining information
+    pub mining_info: RegisterView<Option<MiningInfo>>,
+    pub mined_supply: RegisterView<Amount>,
+    // Burned tokens are removed from total supply
+    pub burned: RegisterView<Amount>,
+    pub reward_split: RegisterView<Option<RewardSplit>>,
+    // Keyed by mining account on this chain
+    pub miner_beneficiaries: MapView<Account, Account>,
+
+    // Mining pool shares, only the last pplns_window shares are kept
+    pub share_index: RegisterView<u64>,
+    pub shares: MapView<u64, MiningShare>,
+    pub share_hashes: MapView<CryptoHash, u64>,
+
+    // Proposer election, owner weights are copied from chain ownership at each election
+    pub election_method: RegisterView<LeaderElectionMethod>,
+    pub leader_election: RegisterView<Option<LeaderElection>>,
+    pub chain_owner_weights: RegisterView<BTreeMap<AccountOwner, u64>>,
+
+    // Staked balance is not counted in balances until it's unstaked and claimed
+    pub stakes: MapView<Account, Amount>,
+    // Stakes summed by owner over its accounts, so election doesn't iterate all stakes
+    pub owner_stakes: MapView<AccountOwner, Amount>,
+    pub total_staked: RegisterView<Amount>,
+    pub unbondings: MapView<Account, Vec<Unbonding>>,
+
+    // Locked creator and team allocations, they're not counted in balances until claimed
+    pub vesting: MapView<Account, Vesting>,
+
+    // Whitelisted presale, purchased tokens are locked until release time
+    pub presale: RegisterView<Option<Presale>>,
+    pub presale_caps: MapView<Account, Amount>,
+    pub presale_purchases: MapView<Account, Amount>,
+
+    // Genesis miner airdrop, vested shares are kept apart from creator and team vesting
+    pub airdropped: RegisterView<Amount>,
+    pub airdrop_vesting: MapView<Account, Vesting>,
+}
+
+// Holder keys start with inverted big endian balance, so keys are ordered by balance descending
+const HOLDER_BALANCE_BYTES: usize = 16;
+
+fn holder_key(holder: Account, balance: Amount) -> Vec<u8> {
+    let mut key = (u128::MAX - u128::from(balance)).to_be_bytes().to_vec();
+    key.extend(bcs::to_bytes(&holder).expect("Failed serialize holder"));
+    key
 }
 
+fn holder_cursor_of(key: &[u8]) -> Result<HolderCursor, ViewError> {
+    let (balance, account) = key.split_at(HOLDER_BALANCE_BYTES);
+    let balance = u128::from_be_bytes(balance.try_into().expect("Invalid holder key"));
+    Ok(HolderCursor {
+        account: bcs::from_bytes(account)?,
+        balance: Amount::from_attos(u128::MAX - balance),
+    })
+}
+
+// Amount received by to after transfer tax, and collected tax with where it really goes
+pub(crate) type Received = (Amount, Option<(TaxDestination, Amount)>);
+
 /// Created meme token will be added to liquidity pool directly
 
 #[allow(dead_code)]
@@ -63,17 +162,23 @@ impl MemeState {
 
         self.initial_liquidity.set(Some(liquidity.clone()));
 
+        self.approve_liquidity(liquidity.fungible_amount, swap_creator_chain_id)
+            .await
+    }
+
+    // Approve holder balance to swap application, then swap application will transfer it to pool
+    pub(crate) async fn approve_liquidity(
+        &mut self,
+        amount: Amount,
+        swap_creator_chain_id: ChainId,
+    ) -> Result<(), MemeError> {
         let swap_application_id = self.swap_application_id.get().unwrap();
         let spender = Account {
             chain_id: swap_creator_chain_id,
             owner: AccountOwner::from(swap_application_id),
         };
-        self.approve(
-            self.holder.get().unwrap(),
-            spender,
-            liquidity.fungible_amount,
-        )
-        .await
+        self.approve(self.holder.get().unwrap(), spender, amount, None)
+            .await
     }
 
     pub(crate) async fn instantiate(
@@ -86,12 +191,44 @@ impl MemeState {
             argument.meme.initial_supply > Amount::ZERO,
             "Invalid initial supply"
         );
-
-        self.initial_owner_balance.set(Amount::from_tokens(100));
+        assert!(
+            argument.meme.mining.initial_difficulty > 0,
+            "Invalid difficulty"
+        );
+        assert!(
+            argument.meme.mining.target_block_time_ms > 0,
+            "Invalid target block time"
+        );
+        assert!(
+            argument.meme.mining.retarget_interval > 0,
+            "Invalid retarget interval"
+        );
+        if let Some(mining_pool) = &argument.meme.mining.mining_pool {
+            mining_pool.validate();
+        }
+        if let Some(emission) = &argument.meme.emission {
+            emission.validate(argument.meme.initial_supply);
+        }
+        if let Some(staking) = &argument.meme.staking {
+            staking.validate();
+        }
+        if let Some(transfer_tax) = &argument.meme.transfer_tax {
+            transfer_tax.validate();
+        }
+        if let Some(reward_split) = &argument.reward_split {
+            reward_split.validate();
+            // Liquidity share could only be topped up to an existing meme native pool
+            assert!(
+                reward_split.liquidity_bps == 0
+                    || (argument.swap_application_id.is_some()
+                        && argument.meme.initial_liquidity.is_some()),
+                "Invalid reward split"
+            );
+        }
 
         self.swap_application_id.set(argument.swap_application_id);
-        self.balances
-            .insert(&application, argument.meme.initial_supply)?;
+        self.set_balance(application, argument.meme.initial_supply)
+            .await?;
         self.holder.set(Some(application));
         self.owner.set(Some(owner));
 
@@ -102,17 +239,19 @@ impl MemeState {
             .set(argument.blob_gateway_application_id);
         self.ams_application_id.set(argument.ams_application_id);
         self.proxy_application_id.set(argument.proxy_application_id);
+        self.reward_split.set(argument.reward_split);
 
-        Ok(())
-    }
+        self.mining_info.set(Some(MiningInfo {
+            difficulty: argument.meme.mining.initial_difficulty,
+            mined_height: None,
+            previous_hash: genesis_hash(application.chain_id),
+            miner: None,
+            mined_blocks: 0,
+            window_started_at: None,
+            window_blocks: 0,
+        }));
 
-    pub(crate) async fn initialize_balance(
-        &mut self,
-        owner: Account,
-        amount: Amount,
-    ) -> Result<(), MemeError> {
-        self.transfer(self.holder.get().unwrap(), owner, amount)
-            .await
+        Ok(())
     }
 
     pub(crate) fn proxy_application_id(&self) -> Option<ApplicationId> {
@@ -131,22 +270,120 @@ impl MemeState {
         *self.swap_application_id.get()
     }
 
-    async fn transfer_(
+    pub(crate) fn set_pool_application(&mut self, pool_application: AccountOwner) {
+        self.pool_application.set(Some(pool_application));
+    }
+
+    pub(crate) fn pool_application(&self) -> Option<AccountOwner> {
+        *self.pool_application.get()
+    }
+
+    pub(crate) fn transfer_tax(&self) -> Option<TransferTax> {
+        self.meme
+            .get()
+            .as_ref()
+            .and_then(|meme| meme.transfer_tax.clone())
+    }
+
+    // Moving funds between chains of the same owner is not a trade, and holder and treasury
+    // are exempted to avoid taxing liquidity, allocations and the tax itself
+    fn transfer_tax_bps(&self, transfer_tax: &TransferTax, from: Account, to: Account) -> u16 {
+        if from.owner == to.owner
+            || Some(from) == *self.holder.get()
+            || transfer_tax
+                .treasury
+                .is_some_and(|treasury| treasury == from || treasury == to)
+        {
+            return 0;
+        }
+        match self.pool_application() {
+            Some(pool_application) if pool_application == to.owner => transfer_tax.sell_bps,
+            Some(pool_application) if pool_application == from.owner => transfer_tax.buy_bps,
+            _ => transfer_tax.transfer_bps,
+        }
+    }
+
+    // Tax is deducted from transferred amount
+    async fn collect_transfer_tax(
         &mut self,
         from: Account,
         to: Account,
         amount: Amount,
-    ) -> Result<(), MemeError> {
-        let from_balance = self.balances.get(&from).await?.unwrap();
+    ) -> Result<Received, MemeError> {
+        let Some(transfer_tax) = self.transfer_tax() else {
+            return Ok((amount, None));
+        };
+        let tax = TransferTax::tax(amount, self.transfer_tax_bps(&transfer_tax, from, to));
+        if tax == Amount::ZERO {
+            return Ok((amount, None));
+        }
 
-        let to_balance = if let Some(balance) = self.balances.get(&to).await? {
-            balance.try_add(amount)?
-        } else {
-            amount
+        let destination = match transfer_tax.destination {
+            TaxDestination::Burn => {
+                self.burn_supply(tax)?;
+                TaxDestination::Burn
+            }
+            TaxDestination::Treasury => {
+                let treasury = transfer_tax.treasury.unwrap();
+                let balance = self.balance_of(treasury).await.try_add(tax)?;
+                self.set_balance(treasury, balance).await?;
+                TaxDestination::Treasury
+            }
+            TaxDestination::Reflection => self.reflect(tax)?,
         };
+        Ok((amount.try_sub(tax)?, Some((destination, tax))))
+    }
 
-        self.balances.insert(&from, from_balance.try_sub(amount)?)?;
-        Ok(self.balances.insert(&to, to_balance)?)
+    // Meme native pool and holder don't take reflections, otherwise pool reserves will drift
+    fn reflects(&self, owner: Account) -> bool {
+        self.transfer_tax()
+            .is_some_and(|transfer_tax| transfer_tax.destination == TaxDestination::Reflection)
+            && Some(owner.owner) != self.pool_application()
+            && Some(owner) != *self.holder.get()
+    }
+
+    // Tax is burned if there's no holder to reflect to, returns where the tax goes
+    fn reflect(&mut self, amount: Amount) -> Result<TaxDestination, MemeError> {
+        let supply = *self.reflection_supply.get();
+        if supply == Amount::ZERO {
+            self.burn_supply(amount)?;
+            return Ok(TaxDestination::Burn);
+        }
+        let reflection_per_token = self
+            .reflection_per_token
+            .get()
+            .saturating_add(TransferTax::reflection_per_token(amount, supply));
+        self.reflection_per_token.set(reflection_per_token);
+        Ok(TaxDestination::Reflection)
+    }
+
+    async fn reflection_of(&self, owner: Account, balance: Amount) -> Amount {
+        if balance == Amount::ZERO || !self.reflects(owner) {
+            return Amount::ZERO;
+        }
+        let debt = self
+            .reflection_debts
+            .get(&owner)
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        TransferTax::reflection_of(balance, self.reflection_per_token.get() - debt)
+    }
+
+    async fn transfer_(
+        &mut self,
+        from: Account,
+        to: Account,
+        amount: Amount,
+    ) -> Result<Received, MemeError> {
+        let from_balance = self.balance_of(from).await;
+        self.set_balance(from, from_balance.try_sub(amount)?)
+            .await?;
+
+        let (received, tax) = self.collect_transfer_tax(from, to, amount).await?;
+        let to_balance = self.balance_of(to).await.try_add(received)?;
+        self.set_balance(to, to_balance).await?;
+        Ok((received, tax))
     }
 
     pub(crate) async fn transfer(
@@ -154,27 +391,62 @@ impl MemeState {
         from: Account,
         to: Account,
         amount: Amount,
-    ) -> Result<(), MemeError> {
+    ) -> Result<Received, MemeError> {
         assert!(amount > Amount::ZERO, "Invalid amount");
         assert!(from != to, "Self transfer");
 
-        let from_balance = self.balances.get(&from).await?.unwrap_or(Amount::ZERO);
+        let from_balance = self.balance_of(from).await;
 
         assert!(from_balance >= amount, "Insufficient balance");
 
         self.transfer_(from, to, amount).await
     }
 
+    // Balance of from is debited once with total amount, so the batch fails as a whole. Returns
+    // what each item received in order
+    pub(crate) async fn batch_transfer(
+        &mut self,
+        from: Account,
+        transfers: Vec<BatchTransferItem>,
+    ) -> Result<Vec<Received>, MemeError> {
+        ensure!(
+            !transfers.is_empty() && transfers.len() <= MAX_BATCH_TRANSFERS,
+            MemeError::InvalidBatchSize
+        );
+
+        let mut total = Amount::ZERO;
+        for transfer in transfers.iter() {
+            ensure!(transfer.amount > Amount::ZERO, MemeError::InvalidAmount);
+            ensure!(transfer.to != from, MemeError::SelfTransfer);
+            total = total.try_add(transfer.amount)?;
+        }
+
+        let from_balance = self.balance_of(from).await;
+        ensure!(from_balance >= total, MemeError::InsufficientFunds);
+        self.set_balance(from, from_balance.try_sub(total)?).await?;
+
+        let mut receipts = Vec::with_capacity(transfers.len());
+        for transfer in transfers {
+            let (received, tax) = self
+                .collect_transfer_tax(from, transfer.to, transfer.amount)
+                .await?;
+            let balance = self.balance_of(transfer.to).await.try_add(received)?;
+            self.set_balance(transfer.to, balance).await?;
+            receipts.push((received, tax));
+        }
+        Ok(receipts)
+    }
+
     pub(crate) async fn transfer_ensure(
         &mut self,
         from: Account,
         to: Account,
         amount: Amount,
-    ) -> Result<(), MemeError> {
+    ) -> Result<Received, MemeError> {
         ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
         ensure!(from != to, MemeError::SelfTransfer);
 
-        let from_balance = self.balances.get(&from).await?.unwrap();
+        let from_balance = self.balance_of(from).await;
 
         ensure!(from_balance >= amount, MemeError::InsufficientFunds);
 
@@ -186,6 +458,7 @@ impl MemeState {
         owner: Account,
         spender: Account,
         amount: Amount,
+        expires_at: Option<Timestamp>,
     ) -> Result<(), MemeError> {
         // Self approve is not allowed
         if owner == spender {
@@ -207,14 +480,26 @@ impl MemeState {
             HashMap::new()
         };
 
-        let spender_allowance = if let Some(allowance) = allowances.get(&spender) {
-            allowance.try_add(amount)?
-        } else {
-            amount
+        // Topped up allowance keeps the stricter expiry, so approval without expiry couldn't
+        // extend an expiring one
+        let spender_allowance = match allowances.get(&spender) {
+            Some(allowance) => Allowance {
+                spender,
+                amount: allowance.amount.try_add(amount)?,
+                expires_at: match (allowance.expires_at, expires_at) {
+                    (Some(existing), Some(expires_at)) => Some(existing.min(expires_at)),
+                    (existing, expires_at) => existing.or(expires_at),
+                },
+            },
+            None => Allowance {
+                spender,
+                amount,
+                expires_at,
+            },
         };
 
-        self.balances
-            .insert(&owner, owner_balance.try_sub(amount)?)?;
+        self.set_balance(owner, owner_balance.try_sub(amount)?)
+            .await?;
 
         allowances.insert(spender, spender_allowance);
         Ok(self.allowances.insert(&owner, allowances)?)
@@ -226,21 +511,211 @@ impl MemeState {
         from: Account,
         to: Account,
         amount: Amount,
-    ) -> Result<(), MemeError> {
+        timestamp: Timestamp,
+    ) -> Result<Received, MemeError> {
         let Some(mut allowances) = self.allowances.get(&from).await? else {
-            panic!("Invalid from");
+            return Err(MemeError::InsufficientAllowance);
+        };
+        let Some(mut allowance) = allowances.get(&owner).cloned() else {
+            return Err(MemeError::InsufficientAllowance);
+        };
+        ensure!(!allowance.expired(timestamp), MemeError::AllowanceExpired);
+        ensure!(allowance.amount >= amount, MemeError::InsufficientAllowance);
+        let (received, tax) = self.collect_transfer_tax(from, to, amount).await?;
+        let balance = self.balance_of(to).await.try_add(received)?;
+        self.set_balance(to, balance).await?;
+        allowance.amount = allowance.amount.try_sub(amount)?;
+        allowances.insert(owner, allowance);
+        self.allowances.insert(&from, allowances)?;
+        Ok((received, tax))
+    }
+
+    // Unspent escrow is credited back to owner balance
+    pub(crate) async fn decrease_allowance(
+        &mut self,
+        owner: Account,
+        spender: Account,
+        amount: Amount,
+    ) -> Result<(), MemeError> {
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+
+        let mut allowances = self.allowances.get(&owner).await?.unwrap_or_default();
+        let Some(mut allowance) = allowances.get(&spender).cloned() else {
+            return Err(MemeError::InsufficientAllowance);
         };
-        let Some(&allowance) = allowances.get(&owner) else {
-            panic!("Invalid owner");
+        ensure!(allowance.amount >= amount, MemeError::InsufficientAllowance);
+
+        allowance.amount = allowance.amount.try_sub(amount)?;
+        if allowance.amount == Amount::ZERO {
+            allowances.remove(&spender);
+        } else {
+            allowances.insert(spender, allowance);
+        }
+        if allowances.is_empty() {
+            self.allowances.remove(&owner)?;
+        } else {
+            self.allowances.insert(&owner, allowances)?;
+        }
+
+        let balance = self.balance_of(owner).await.try_add(amount)?;
+        self.set_balance(owner, balance).await
+    }
+
+    pub(crate) async fn revoke_allowance(
+        &mut self,
+        owner: Account,
+        spender: Account,
+    ) -> Result<Amount, MemeError> {
+        let allowance = self.allowance_of(owner, spender).await;
+        ensure!(allowance > Amount::ZERO, MemeError::InsufficientAllowance);
+
+        self.decrease_allowance(owner, spender, allowance).await?;
+        Ok(allowance)
+    }
+
+    pub(crate) async fn allowances_of(&self, owner: Account) -> Result<Vec<Allowance>, MemeError> {
+        let mut allowances = self
+            .allowances
+            .get(&owner)
+            .await?
+            .unwrap_or_default()
+            .into_values()
+            .collect::<Vec<_>>();
+        // HashMap order is not deterministic
+        allowances.sort_by_key(|allowance| allowance.spender);
+        Ok(allowances)
+    }
+
+    pub(crate) async fn add_pending_operation(
+        &mut self,
+        owner: Account,
+        kind: PendingOperationKind,
+        from: Account,
+        to: Option<Account>,
+        amount: Amount,
+        timestamp: Timestamp,
+    ) -> Result<u64, MemeError> {
+        let id = *self.pending_operation_id.get();
+        self.pending_operation_id.set(id + 1);
+
+        let mut operations = self.pending_operations_of(owner).await?;
+        operations.push(PendingOperation {
+            id,
+            kind,
+            from,
+            to,
+            amount,
+            created_at: timestamp,
+            status: PendingOperationStatus::Pending,
+            reason: None,
+        });
+        // Drop oldest resolved operations, pending ones are always kept
+        while operations.len() > MAX_PENDING_OPERATIONS {
+            let Some(index) = operations
+                .iter()
+                .position(|operation| operation.status != PendingOperationStatus::Pending)
+            else {
+                break;
+            };
+            operations.remove(index);
+        }
+        self.pending_operations.insert(&owner, operations)?;
+        Ok(id)
+    }
+
+    // Messages between two chains are executed in order, so replies resolve pending operations
+    // from the oldest one
+    pub(crate) async fn resolve_pending_operation(
+        &mut self,
+        owner: Account,
+        status: PendingOperationStatus,
+        reason: Option<String>,
+    ) -> Result<(), MemeError> {
+        let mut operations = self.pending_operations_of(owner).await?;
+        let Some(operation) = operations
+            .iter_mut()
+            .find(|operation| operation.status == PendingOperationStatus::Pending)
+        else {
+            return Err(MemeError::NoPendingOperation);
         };
-        assert!(allowance >= amount, "Insufficient allowance");
-        let balance = match self.balances.get(&to).await? {
-            Some(balance) => balance.try_add(amount)?,
-            _ => amount,
+        operation.status = status;
+        operation.reason = reason;
+        Ok(self.pending_operations.insert(&owner, operations)?)
+    }
+
+    pub(crate) async fn pending_operations_of(
+        &self,
+        owner: Account,
+    ) -> Result<Vec<PendingOperation>, MemeError> {
+        Ok(self
+            .pending_operations
+            .get(&owner)
+            .await?
+            .unwrap_or_default())
+    }
+
+    pub(crate) async fn permit_nonce(&self, owner: Account) -> Result<u64, MemeError> {
+        Ok(self.permit_nonces.get(&owner).await?.unwrap_or_default())
+    }
+
+    // Signature is verified by caller, here we only check it's still valid and not replayed
+    pub(crate) async fn permit(
+        &mut self,
+        permit: Permit,
+        timestamp: Timestamp,
+    ) -> Result<(), MemeError> {
+        ensure!(timestamp < permit.deadline, MemeError::PermitExpired);
+        ensure!(
+            permit
+                .expires_at
+                .is_none_or(|expires_at| timestamp < expires_at),
+            MemeError::InvalidExpiry
+        );
+
+        let nonce = self.permit_nonce(permit.owner).await?;
+        ensure!(permit.nonce == nonce, MemeError::InvalidPermitNonce);
+
+        // Nonce is only consumed by applied permit, so a rejected one could be resubmitted
+        self.approve(
+            permit.owner,
+            permit.spender,
+            permit.amount,
+            permit.expires_at,
+        )
+        .await?;
+        Ok(self.permit_nonces.insert(&permit.owner, nonce + 1)?)
+    }
+
+    // Escrow of expired allowances is credited back to owner balance
+    pub(crate) async fn reclaim_expired_allowances(
+        &mut self,
+        owner: Account,
+        timestamp: Timestamp,
+    ) -> Result<Amount, MemeError> {
+        let Some(allowances) = self.allowances.get(&owner).await? else {
+            return Ok(Amount::ZERO);
         };
-        self.balances.insert(&to, balance)?;
-        allowances.insert(owner, allowance.try_sub(amount)?);
-        Ok(self.allowances.insert(&from, allowances)?)
+        let (expired, allowances): (HashMap<_, _>, HashMap<_, _>) = allowances
+            .into_iter()
+            .partition(|(_, allowance)| allowance.expired(timestamp));
+        if expired.is_empty() {
+            return Ok(Amount::ZERO);
+        }
+
+        let mut amount = Amount::ZERO;
+        for allowance in expired.values() {
+            amount = amount.try_add(allowance.amount)?;
+        }
+
+        if allowances.is_empty() {
+            self.allowances.remove(&owner)?;
+        } else {
+            self.allowances.insert(&owner, allowances)?;
+        }
+
+        let balance = self.balance_of(owner).await.try_add(amount)?;
+        self.set_balance(owner, balance).await?;
+        Ok(amount)
     }
 
     pub(crate) async fn owner(&mut self) -> Account {
@@ -251,25 +726,298 @@ impl MemeState {
         self.owner.get().unwrap().owner
     }
 
+    // All balance changes must go through here to keep holders index, snapshots and reflections
+    // in sync. Balance is read with balance_of, so pending reflection is settled into it
+    async fn set_balance(&mut self, owner: Account, balance: Amount) -> Result<(), MemeError> {
+        let stored = self.balances.get(&owner).await?.unwrap_or_default();
+        self.checkpoint_balance(owner, stored).await?;
+        self.settle_reflection(owner, stored, balance)?;
+        let mut holder_count = *self.holder_count.get();
+
+        if stored > Amount::ZERO {
+            self.holders.remove(holder_key(owner, stored));
+            holder_count -= 1;
+        }
+        if balance > Amount::ZERO {
+            self.holders.insert(holder_key(owner, balance), ());
+            holder_count += 1;
+        }
+
+        self.holder_count.set(holder_count);
+        Ok(self.balances.insert(&owner, balance)?)
+    }
+
+    fn settle_reflection(
+        &mut self,
+        owner: Account,
+        stored: Amount,
+        balance: Amount,
+    ) -> Result<(), MemeError> {
+        if !self.reflects(owner) {
+            return Ok(());
+        }
+        let supply = self
+            .reflection_supply
+            .get()
+            .try_sub(stored)?
+            .try_add(balance)?;
+        self.reflection_supply.set(supply);
+
+        let reflection_per_token = *self.reflection_per_token.get();
+        if reflection_per_token > 0 {
+            self.reflection_debts.insert(&owner, reflection_per_token)?;
+        }
+        Ok(())
+    }
+
+    fn checkpointed(checkpoints: &[Checkpoint], snapshot_id: u64) -> bool {
+        snapshot_id == 0
+            || checkpoints
+                .last()
+                .is_some_and(|checkpoint| checkpoint.snapshot_id >= snapshot_id)
+    }
+
+    fn checkpoint(checkpoints: &mut Vec<Checkpoint>, snapshot_id: u64, value: Amount) -> bool {
+        if Self::checkpointed(checkpoints, snapshot_id) {
+            return false;
+        }
+        checkpoints.push(Checkpoint { snapshot_id, value });
+        true
+    }
+
+    // Stored balance is not changed since snapshot, so it's settled with reflections until then
+    async fn checkpoint_balance(
+        &mut self,
+        owner: Account,
+        stored: Amount,
+    ) -> Result<(), MemeError> {
+        let snapshot_id = *self.snapshot_id.get();
+        if snapshot_id == 0 {
+            return Ok(());
+        }
+        let mut checkpoints = self
+            .balance_checkpoints
+            .get(&owner)
+            .await?
+            .unwrap_or_default();
+        if Self::checkpointed(&checkpoints, snapshot_id) {
+            return Ok(());
+        }
+        let previous = self.settled_balance_at(owner, stored, snapshot_id).await?;
+        Self::checkpoint(&mut checkpoints, snapshot_id, previous);
+        Ok(self.balance_checkpoints.insert(&owner, checkpoints)?)
+    }
+
+    fn set_total_supply(&mut self, total_supply: Amount) {
+        let mut meme = self.meme();
+        let snapshot_id = *self.snapshot_id.get();
+        let mut checkpoints = self.total_supply_checkpoints.get().clone();
+        if Self::checkpoint(&mut checkpoints, snapshot_id, meme.total_supply) {
+            self.total_supply_checkpoints.set(checkpoints);
+        }
+
+        meme.total_supply = total_supply;
+        self.meme.set(Some(meme));
+    }
+
+    pub(crate) fn snapshot(&mut self, timestamp: Timestamp) -> Result<u64, MemeError> {
+        let id = *self.snapshot_id.get() + 1;
+        self.snapshot_id.set(id);
+        self.snapshot_reflections
+            .insert(&id, *self.reflection_per_token.get())?;
+        self.snapshots.insert(
+            &id,
+            Snapshot {
+                id,
+                created_at: timestamp,
+            },
+        )?;
+        Ok(id)
+    }
+
+    pub(crate) async fn snapshot_of(&self, id: u64) -> Result<Option<Snapshot>, MemeError> {
+        Ok(self.snapshots.get(&id).await?)
+    }
+
+    fn ensure_snapshot(&self, snapshot_id: u64) -> Result<(), MemeError> {
+        ensure!(
+            snapshot_id > 0 && snapshot_id <= *self.snapshot_id.get(),
+            MemeError::InvalidSnapshot
+        );
+        Ok(())
+    }
+
+    // Staked, vesting and presale locked tokens are not in balance so they're not counted here
+    pub(crate) async fn balance_of_at(
+        &self,
+        owner: Account,
+        snapshot_id: u64,
+    ) -> Result<Amount, MemeError> {
+        self.ensure_snapshot(snapshot_id)?;
+        let checkpoints = self
+            .balance_checkpoints
+            .get(&owner)
+            .await?
+            .unwrap_or_default();
+        // Without checkpoint balance is not changed since snapshot, but reflections accrued
+        // after snapshot are not counted
+        let stored = self.balances.get(&owner).await?.unwrap_or_default();
+        Ok(Checkpoint::value_at(
+            &checkpoints,
+            snapshot_id,
+            self.settled_balance_at(owner, stored, snapshot_id).await?,
+        ))
+    }
+
+    // Stored balance must not be changed since snapshot was taken
+    async fn settled_balance_at(
+        &self,
+        owner: Account,
+        stored: Amount,
+        snapshot_id: u64,
+    ) -> Result<Amount, MemeError> {
+        if stored == Amount::ZERO || !self.reflects(owner) {
+            return Ok(stored);
+        }
+        let reflection_per_token = self
+            .snapshot_reflections
+            .get(&snapshot_id)
+            .await?
+            .unwrap_or_default();
+        // Debt is raised above snapshot accumulator by reflection settled after snapshot, nothing
+        // was pending at snapshot then
+        let debt = self.reflection_debts.get(&owner).await?.unwrap_or_default();
+        Ok(stored.try_add(TransferTax::reflection_of(
+            stored,
+            reflection_per_token.saturating_sub(debt),
+        ))?)
+    }
+
+    pub(crate) fn total_supply_at(&self, snapshot_id: u64) -> Result<Amount, MemeError> {
+        self.ensure_snapshot(snapshot_id)?;
+        Ok(Checkpoint::value_at(
+            self.total_supply_checkpoints.get(),
+            snapshot_id,
+            self.meme().total_supply,
+        ))
+    }
+
+    pub(crate) fn holder_count(&self) -> u64 {
+        *self.holder_count.get()
+    }
+
+    // Holders after cursor ordered by stored balance descending, each with its settled balance
+    // and its own cursor. Holders with stored balance under min balance are not returned
+    pub(crate) async fn holders(
+        &self,
+        first: usize,
+        after: Option<HolderCursor>,
+        min_balance: Amount,
+    ) -> Result<Vec<(Holder, HolderCursor)>, MemeError> {
+        let mut holders = Vec::new();
+        for cursor in self.holder_cursors(first, after, min_balance).await? {
+            let holder = Holder {
+                account: cursor.account,
+                balance: self.balance_of(cursor.account).await,
+            };
+            holders.push((holder, cursor));
+        }
+        Ok(holders)
+    }
+
+    // Index is scanned by prefixes of the cursor key from the longest one, so holders before
+    // cursor are only visited when they share the scanned prefix, instead of walking the index
+    // from the top
+    async fn holder_cursors(
+        &self,
+        first: usize,
+        after: Option<HolderCursor>,
+        min_balance: Amount,
+    ) -> Result<Vec<HolderCursor>, MemeError> {
+        let mut cursors = Vec::new();
+        if first == 0 {
+            return Ok(cursors);
+        }
+        let Some(after) = after else {
+            self.scan_holders(Vec::new(), None, first, min_balance, &mut cursors)
+                .await?;
+            return Ok(cursors);
+        };
+
+        let after = holder_key(after.account, after.balance);
+        // Holders of the same balance are followed by balances differing in the lowest byte
+        for len in std::iter::once(HOLDER_BALANCE_BYTES).chain((0..HOLDER_BALANCE_BYTES).rev()) {
+            let prefix = after[..len].to_vec();
+            if !self
+                .scan_holders(prefix, Some(&after), first, min_balance, &mut cursors)
+                .await?
+            {
+                break;
+            }
+        }
+        Ok(cursors)
+    }
+
+    // Appends holders with keys after cursor under prefix, returns false when page is complete.
+    // Keys sharing a longer prefix with cursor are already scanned
+    async fn scan_holders(
+        &self,
+        prefix: Vec<u8>,
+        after: Option<&[u8]>,
+        first: usize,
+        min_balance: Amount,
+        cursors: &mut Vec<HolderCursor>,
+    ) -> Result<bool, MemeError> {
+        let len = prefix.len();
+        let mut complete = false;
+        self.holders
+            .for_each_key_while(
+                |suffix| {
+                    let key = [prefix.as_slice(), suffix].concat();
+                    if let Some(after) = after {
+                        if key.as_slice() <= after
+                            || (len < HOLDER_BALANCE_BYTES && key[len] == after[len])
+                        {
+                            return Ok(true);
+                        }
+                    }
+                    let cursor = holder_cursor_of(&key)?;
+                    complete = cursor.balance < min_balance;
+                    if complete {
+                        return Ok(false);
+                    }
+                    cursors.push(cursor);
+                    complete = cursors.len() >= first;
+                    Ok(!complete)
+                },
+                prefix.clone(),
+            )
+            .await?;
+        Ok(!complete)
+    }
+
     pub(crate) async fn balance_of(&self, owner: Account) -> Amount {
-        match self.balances.get(&owner).await.unwrap() {
+        let balance = match self.balances.get(&owner).await.unwrap() {
             Some(amount) => amount,
             _ => Amount::ZERO,
-        }
+        };
+        balance.saturating_add(self.reflection_of(owner, balance).await)
     }
 
     pub(crate) async fn allowance_of(&self, owner: Account, spender: Account) -> Amount {
         match self.allowances.get(&owner).await.unwrap() {
             Some(allowances) => match allowances.get(&spender) {
-                Some(&amount) => amount,
+                Some(allowance) => allowance.amount,
                 _ => Amount::ZERO,
             },
             _ => Amount::ZERO,
         }
     }
 
-    pub(crate) async fn initial_owner_balance(&self) -> Amount {
-        *self.initial_owner_balance.get()
+    pub(crate) fn update_metadata(&mut self, metadata: Metadata) {
+        let mut meme = self.meme();
+        meme.metadata = metadata;
+        self.meme.set(Some(meme));
     }
 
     pub(crate) async fn transfer_ownership(
@@ -333,4 +1081,931 @@ impl MemeState {
     pub(crate) fn meme(&self) -> Meme {
         self.meme.get().as_ref().unwrap().clone()
     }
+
+    pub(crate) fn mining_info(&self) -> MiningInfo {
+        self.mining_info.get().as_ref().unwrap().clone()
+    }
+
+    pub(crate) async fn mine(
+        &mut self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        miner: Account,
+        nonce: CryptoHash,
+        timestamp: Timestamp,
+    ) -> Result<(), MemeError> {
+        let mut mining_info = self.mining_info();
+
+        // Only one mine could be accepted at each height
+        if let Some(mined_height) = mining_info.mined_height {
+            ensure!(height > mined_height, MemeError::AlreadyMined);
+        }
+
+        let algorithm = self.meme().mining.pow_algorithm;
+        let challenge = MiningChallenge {
+            chain_id,
+            height,
+            previous_hash: mining_info.previous_hash,
+            miner,
+        };
+        ensure!(
+            challenge.verify(&algorithm, nonce, mining_info.difficulty),
+            MemeError::InvalidNonce
+        );
+
+        let hash = challenge.hash(&algorithm, nonce);
+
+        // Mined nonce is also a share if it's not submitted before
+        if let Some(mining_pool) = self.mining_pool() {
+            if !self.share_hashes.contains_key(&hash).await? {
+                let difficulty = mining_pool.share_difficulty(mining_info.difficulty);
+                self.add_share(miner, hash, height, difficulty, mining_pool.pplns_window)
+                    .await?;
+            }
+        }
+
+        mining_info.mined_height = Some(height);
+        mining_info.previous_hash = hash;
+        mining_info.miner = Some(miner);
+        mining_info.mined_blocks += 1;
+
+        self.retarget(&mut mining_info, timestamp);
+        self.mining_info.set(Some(mining_info));
+
+        Ok(())
+    }
+
+    fn retarget(&self, mining_info: &mut MiningInfo, timestamp: Timestamp) {
+        let Some(window_started_at) = mining_info.window_started_at else {
+            // The first mined block starts the first window
+            mining_info.window_started_at = Some(timestamp);
+            return;
+        };
+
+        mining_info.window_blocks += 1;
+
+        let mining = &self.meme.get().as_ref().unwrap().mining;
+        if mining_info.window_blocks < mining.retarget_interval {
+            return;
+        }
+
+        let expected_micros = mining
+            .target_block_time_ms
+            .saturating_mul(1000)
+            .saturating_mul(mining_info.window_blocks);
+        let actual_micros = timestamp
+            .micros()
+            .saturating_sub(window_started_at.micros());
+
+        mining_info.difficulty =
+            retarget_difficulty(mining_info.difficulty, expected_micros, actual_micros);
+        mining_info.window_started_at = Some(timestamp);
+        mining_info.window_blocks = 0;
+    }
+
+    pub(crate) fn difficulty(&self) -> u64 {
+        self.mining_info().difficulty
+    }
+
+    pub(crate) fn target_block_time_ms(&self) -> u64 {
+        self.meme
+            .get()
+            .as_ref()
+            .unwrap()
+            .mining
+            .target_block_time_ms
+    }
+
+    pub(crate) fn mined_supply(&self) -> Amount {
+        *self.mined_supply.get()
+    }
+
+    pub(crate) fn remaining_supply(&self) -> Amount {
+        let meme = self.meme.get().as_ref().unwrap();
+        let Some(emission) = &meme.emission else {
+            return Amount::ZERO;
+        };
+        emission
+            .hard_cap
+            .saturating_sub(meme.initial_supply)
+            .saturating_sub(self.mined_supply())
+    }
+
+    // Reward of the next mined block
+    pub(crate) fn current_block_reward(&self) -> Amount {
+        let Some(emission) = &self.meme.get().as_ref().unwrap().emission else {
+            return Amount::ZERO;
+        };
+        emission
+            .block_reward(self.mining_info().mined_blocks)
+            .min(self.remaining_supply())
+    }
+
+    pub(crate) async fn mint(&mut self, to: Account, amount: Amount) -> Result<(), MemeError> {
+        if amount == Amount::ZERO {
+            return Ok(());
+        }
+        ensure!(amount <= self.remaining_supply(), MemeError::InvalidAmount);
+
+        let balance = self.balance_of(to).await.try_add(amount)?;
+        self.set_balance(to, balance).await?;
+
+        let total_supply = self.meme().total_supply.try_add(amount)?;
+        self.set_total_supply(total_supply);
+
+        self.mined_supply.set(self.mined_supply().try_add(amount)?);
+        Ok(())
+    }
+
+    pub(crate) fn burned(&self) -> Amount {
+        *self.burned.get()
+    }
+
+    fn burn_supply(&mut self, amount: Amount) -> Result<(), MemeError> {
+        let total_supply = self.meme().total_supply.try_sub(amount)?;
+        self.set_total_supply(total_supply);
+
+        self.burned.set(self.burned().try_add(amount)?);
+        Ok(())
+    }
+
+    pub(crate) async fn burn(&mut self, owner: Account, amount: Amount) -> Result<(), MemeError> {
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+
+        let balance = self.balance_of(owner).await;
+        ensure!(balance >= amount, MemeError::InsufficientFunds);
+
+        self.set_balance(owner, balance.try_sub(amount)?).await?;
+        self.burn_supply(amount)
+    }
+
+    // Allowance is already deducted from balance of from when it's approved
+    pub(crate) async fn burn_from(
+        &mut self,
+        owner: Account,
+        from: Account,
+        amount: Amount,
+        timestamp: Timestamp,
+    ) -> Result<(), MemeError> {
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+
+        let mut allowances = self.allowances.get(&from).await?.unwrap_or_default();
+        let Some(mut allowance) = allowances.get(&owner).cloned() else {
+            return Err(MemeError::InsufficientAllowance);
+        };
+        ensure!(!allowance.expired(timestamp), MemeError::AllowanceExpired);
+        ensure!(allowance.amount >= amount, MemeError::InsufficientAllowance);
+
+        allowance.amount = allowance.amount.try_sub(amount)?;
+        allowances.insert(owner, allowance);
+        self.allowances.insert(&from, allowances)?;
+        self.burn_supply(amount)
+    }
+
+    pub(crate) fn reward_split(&self) -> Option<RewardSplit> {
+        self.reward_split.get().clone()
+    }
+
+    pub(crate) fn reward_shares(&self, reward: Amount) -> RewardShares {
+        match self.reward_split.get() {
+            Some(reward_split) => reward_split.split(reward),
+            None => RewardShares {
+                proposer: reward,
+                ..Default::default()
+            },
+        }
+    }
+
+    // Proposer share is minted by caller after it's settled with mining pool shares. Liquidity
+    // share is minted to holder, it'll be approved to swap application by caller. Returns the
+    // minted shares which are not zero
+    pub(crate) async fn mint_reward(
+        &mut self,
+        creator: Account,
+        shares: RewardShares,
+    ) -> Result<Vec<(Account, Amount)>, MemeError> {
+        let mut minted = vec![(creator, shares.creator)];
+        if let Some(treasury) = self.reward_split().and_then(|split| split.treasury) {
+            minted.push((treasury, shares.treasury));
+        }
+        minted.push((self.holder.get().unwrap(), shares.liquidity));
+        minted.retain(|(_, amount)| *amount > Amount::ZERO);
+
+        for (to, amount) in &minted {
+            self.mint(*to, *amount).await?;
+        }
+        Ok(minted)
+    }
+
+    pub(crate) async fn set_miner_beneficiary(
+        &mut self,
+        miner: Account,
+        beneficiary: Option<Account>,
+    ) -> Result<(), MemeError> {
+        match beneficiary {
+            Some(beneficiary) => Ok(self.miner_beneficiaries.insert(&miner, beneficiary)?),
+            None => Ok(self.miner_beneficiaries.remove(&miner)?),
+        }
+    }
+
+    pub(crate) async fn miner_beneficiary(
+        &self,
+        miner: Account,
+    ) -> Result<Option<Account>, MemeError> {
+        Ok(self.miner_beneficiaries.get(&miner).await?)
+    }
+
+    pub(crate) fn mining_pool(&self) -> Option<MiningPool> {
+        self.meme.get().as_ref().unwrap().mining.mining_pool.clone()
+    }
+
+    pub(crate) fn share_difficulty(&self) -> Option<u64> {
+        self.mining_pool()
+            .map(|mining_pool| mining_pool.share_difficulty(self.difficulty()))
+    }
+
+    async fn add_share(
+        &mut self,
+        miner: Account,
+        hash: CryptoHash,
+        height: BlockHeight,
+        difficulty: u64,
+        pplns_window: u64,
+    ) -> Result<(), MemeError> {
+        let index = *self.share_index.get();
+
+        self.shares.insert(
+            &index,
+            MiningShare {
+                miner,
+                hash,
+                height,
+                difficulty,
+            },
+        )?;
+        self.share_hashes.insert(&hash, index)?;
+        self.share_index.set(index + 1);
+
+        // Shares out of PPLNS window won't be rewarded any more
+        let Some(evict_index) = (index + 1).checked_sub(pplns_window + 1) else {
+            return Ok(());
+        };
+        if let Some(share) = self.shares.get(&evict_index).await? {
+            self.share_hashes.remove(&share.hash)?;
+            self.shares.remove(&evict_index)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn submit_share(
+        &mut self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        miner: Account,
+        nonce: CryptoHash,
+    ) -> Result<(), MemeError> {
+        let Some(mining_pool) = self.mining_pool() else {
+            return Err(MemeError::NotMiningPool);
+        };
+        let mining_info = self.mining_info();
+        let algorithm = self.meme().mining.pow_algorithm;
+
+        let challenge = MiningChallenge {
+            chain_id,
+            height,
+            previous_hash: mining_info.previous_hash,
+            miner,
+        };
+        let difficulty = mining_pool.share_difficulty(mining_info.difficulty);
+        ensure!(
+            challenge.verify(&algorithm, nonce, difficulty),
+            MemeError::InvalidNonce
+        );
+
+        let hash = challenge.hash(&algorithm, nonce);
+        ensure!(
+            !self.share_hashes.contains_key(&hash).await?,
+            MemeError::DuplicateShare
+        );
+
+        self.add_share(miner, hash, height, difficulty, mining_pool.pplns_window)
+            .await
+    }
+
+    pub(crate) async fn pplns_shares(&self) -> Result<Vec<MiningShare>, MemeError> {
+        let Some(mining_pool) = self.mining_pool() else {
+            return Ok(Vec::new());
+        };
+        let share_index = *self.share_index.get();
+        let start = share_index.saturating_sub(mining_pool.pplns_window);
+
+        let mut shares = Vec::new();
+        for index in start..share_index {
+            if let Some(share) = self.shares.get(&index).await? {
+                shares.push(share);
+            }
+        }
+        Ok(shares)
+    }
+
+    // Split proposer reward by shares in PPLNS window weighted by their difficulties, so shares
+    // submitted before a retarget are paid by the work they proved. Rounding dust goes to miner
+    // of the block
+    pub(crate) async fn settle_shares(
+        &self,
+        miner: Account,
+        reward: Amount,
+    ) -> Result<Vec<(Account, Amount)>, MemeError> {
+        let shares = self.pplns_shares().await?;
+        if shares.is_empty() {
+            return Ok(vec![(miner, reward)]);
+        }
+
+        let mut weights = BTreeMap::new();
+        let mut total_weight = 0u128;
+        for share in shares.iter() {
+            let weight = u128::from(share.difficulty.max(1));
+            *weights.entry(share.miner).or_insert(0u128) += weight;
+            total_weight += weight;
+        }
+
+        let mut rewards = Vec::new();
+        let mut settled = Amount::ZERO;
+
+        for (member, weight) in weights {
+            let amount = Amount::from_attos(
+                u128::from(reward)
+                    .checked_mul(weight)
+                    .ok_or(ArithmeticError::Overflow)?
+                    / total_weight,
+            );
+            settled = settled.try_add(amount)?;
+            rewards.push((member, amount));
+        }
+        rewards.push((miner, reward.try_sub(settled)?));
+
+        Ok(rewards)
+    }
+
+    pub(crate) fn initialize_leader_election(
+        &mut self,
+        method: LeaderElectionMethod,
+        height: BlockHeight,
+        timestamp: Timestamp,
+    ) {
+        self.election_method.set(method);
+        let previous_hash = self.mining_info().previous_hash;
+        self.leader_election
+            .set(Some(LeaderElection::new(height, timestamp, previous_hash)));
+    }
+
+    pub(crate) fn election_method(&self) -> LeaderElectionMethod {
+        *self.election_method.get()
+    }
+
+    pub(crate) fn leader_election(&self) -> Option<LeaderElection> {
+        self.leader_election.get().clone()
+    }
+
+    // Election of the next height is seeded by the latest mined hash
+    fn election_hash(&self, height: BlockHeight) -> Option<CryptoHash> {
+        self.leader_election().map(|election| {
+            if height > election.height {
+                self.mining_info().previous_hash
+            } else {
+                election.previous_hash
+            }
+        })
+    }
+
+    // Leader is only elected by meme application in multi leader rounds. With proof of stake,
+    // chain owners elect leader of the first round by stake once any of them staked. Later rounds
+    // fall back to round robin of all chain owners, so an offline staker won't freeze the chain
+    pub(crate) async fn leader_for(
+        &self,
+        height: BlockHeight,
+        round: u32,
+    ) -> Result<Option<AccountOwner>, MemeError> {
+        match self.election_method() {
+            LeaderElectionMethod::SingleLeaderRoundRobin | LeaderElectionMethod::ProofOfWork => {
+                return Ok(None)
+            }
+            LeaderElectionMethod::ProofOfStake if round == 0 => {
+                let stakes = self.proposer_stakes().await?;
+                if !stakes.is_empty() {
+                    if let Some(previous_hash) = self.election_hash(height) {
+                        let seed = election_seed(height, round, previous_hash);
+                        return Ok(stake_weighted_leader(&stakes, seed));
+                    }
+                }
+            }
+            LeaderElectionMethod::ProofOfStake | LeaderElectionMethod::MultiLeaderRoundRobin => {}
+        }
+        Ok(round_robin_leader(
+            self.chain_owner_weights.get(),
+            height,
+            round,
+        ))
+    }
+
+    // With proof of work, the block should be mined by its proposer before other operations.
+    // Only meme operations proposed directly by chain owners reach here, so the rule applies to
+    // block proposal. Incoming messages and calls from other applications are not restricted
+    fn ensure_mined_by(
+        &self,
+        height: BlockHeight,
+        signer: Option<AccountOwner>,
+    ) -> Result<(), MemeError> {
+        let mining_info = self.mining_info();
+        ensure!(
+            mining_info.mined_height == Some(height)
+                && mining_info.miner.map(|miner| miner.owner) == signer,
+            MemeError::InvalidProposer
+        );
+        Ok(())
+    }
+
+    // Round is decided by time passed since the previous block, so every owner elects the same
+    // leader for a block. Chain without weighted owners doesn't elect leader.
+    // Limitations: application only executes blocks carrying meme operations, so blocks without
+    // them are not checked and the election only guards who could change meme state, not who
+    // could extend the chain. Block timestamp is chosen by the proposer, validators only reject
+    // timestamps in the future, so a proposer could wait for its own round but couldn't jump to it
+    pub(crate) async fn elect_proposer(
+        &mut self,
+        owner_weights: BTreeMap<AccountOwner, u64>,
+        height: BlockHeight,
+        timestamp: Timestamp,
+        timeout_config: &TimeoutConfig,
+        signer: Option<AccountOwner>,
+        mining: bool,
+        sharing: bool,
+    ) -> Result<(), MemeError> {
+        let previous_hash = self.mining_info().previous_hash;
+        let mut election =
+            self.leader_election()
+                .unwrap_or(LeaderElection::new(height, timestamp, previous_hash));
+        election.advance(height, timestamp, previous_hash);
+
+        let round = election_round(
+            timestamp
+                .micros()
+                .saturating_sub(election.round_started_at.micros()),
+            timeout_config.base_timeout.as_micros(),
+            timeout_config.timeout_increment.as_micros(),
+        );
+
+        self.leader_election.set(Some(election));
+        self.chain_owner_weights.set(owner_weights);
+
+        // Shares are verified against share difficulty, any miner of the pool could submit them
+        if sharing {
+            return Ok(());
+        }
+        if self.election_method() == LeaderElectionMethod::ProofOfWork
+            && !self.chain_owner_weights.get().is_empty()
+        {
+            // Mine operation verifies its nonce itself
+            if mining {
+                return Ok(());
+            }
+            return self.ensure_mined_by(height, signer);
+        }
+
+        let Some(leader) = self.leader_for(height, round).await? else {
+            return Ok(());
+        };
+        ensure!(signer == Some(leader), MemeError::InvalidProposer);
+        Ok(())
+    }
+
+    pub(crate) fn staking(&self) -> Option<StakingConfig> {
+        self.meme().staking
+    }
+
+    pub(crate) fn total_staked(&self) -> Amount {
+        *self.total_staked.get()
+    }
+
+    pub(crate) async fn stake_of(&self, owner: Account) -> Result<Amount, MemeError> {
+        Ok(self.stakes.get(&owner).await?.unwrap_or(Amount::ZERO))
+    }
+
+    pub(crate) async fn unbondings_of(&self, owner: Account) -> Result<Vec<Unbonding>, MemeError> {
+        Ok(self.unbondings.get(&owner).await?.unwrap_or_default())
+    }
+
+    // Stakes grouped by owner. Only chain owners could propose blocks, so other stakes don't
+    // join election
+    async fn proposer_stakes(&self) -> Result<BTreeMap<AccountOwner, Amount>, MemeError> {
+        let mut stakes = BTreeMap::new();
+
+        for owner in self.chain_owner_weights.get().keys() {
+            if let Some(stake) = self.owner_stakes.get(owner).await? {
+                stakes.insert(*owner, stake);
+            }
+        }
+        Ok(stakes)
+    }
+
+    async fn add_owner_stake(
+        &mut self,
+        owner: AccountOwner,
+        amount: Amount,
+    ) -> Result<(), MemeError> {
+        let stake = self.owner_stakes.get(&owner).await?.unwrap_or(Amount::ZERO);
+        Ok(self.owner_stakes.insert(&owner, stake.try_add(amount)?)?)
+    }
+
+    async fn sub_owner_stake(
+        &mut self,
+        owner: AccountOwner,
+        amount: Amount,
+    ) -> Result<(), MemeError> {
+        let stake = self
+            .owner_stakes
+            .get(&owner)
+            .await?
+            .unwrap_or(Amount::ZERO)
+            .try_sub(amount)?;
+        if stake == Amount::ZERO {
+            Ok(self.owner_stakes.remove(&owner)?)
+        } else {
+            Ok(self.owner_stakes.insert(&owner, stake)?)
+        }
+    }
+
+    pub(crate) async fn stake(&mut self, owner: Account, amount: Amount) -> Result<(), MemeError> {
+        let Some(staking) = self.staking() else {
+            return Err(MemeError::StakingDisabled);
+        };
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+
+        let balance = self.balance_of(owner).await;
+        ensure!(balance >= amount, MemeError::InsufficientFunds);
+
+        let stake = self.stake_of(owner).await?.try_add(amount)?;
+        ensure!(stake >= staking.min_stake, MemeError::InsufficientStake);
+
+        self.set_balance(owner, balance.try_sub(amount)?).await?;
+        self.stakes.insert(&owner, stake)?;
+        self.add_owner_stake(owner.owner, amount).await?;
+        self.total_staked.set(self.total_staked().try_add(amount)?);
+        Ok(())
+    }
+
+    pub(crate) async fn unstake(
+        &mut self,
+        owner: Account,
+        amount: Amount,
+        timestamp: Timestamp,
+    ) -> Result<(), MemeError> {
+        let Some(staking) = self.staking() else {
+            return Err(MemeError::StakingDisabled);
+        };
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+
+        let stake = self.stake_of(owner).await?;
+        ensure!(stake >= amount, MemeError::InsufficientStake);
+
+        // Remaining stake should still meet minimum stake unless it's fully unstaked
+        let stake = stake.try_sub(amount)?;
+        ensure!(
+            stake == Amount::ZERO || stake >= staking.min_stake,
+            MemeError::InsufficientStake
+        );
+
+        if stake == Amount::ZERO {
+            self.stakes.remove(&owner)?;
+        } else {
+            self.stakes.insert(&owner, stake)?;
+        }
+        self.sub_owner_stake(owner.owner, amount).await?;
+        self.total_staked.set(self.total_staked().try_sub(amount)?);
+
+        let mut unbondings = self.unbondings_of(owner).await?;
+        unbondings.push(Unbonding {
+            amount,
+            release_at: timestamp
+                .saturating_add(TimeDelta::from_millis(staking.unbonding_period_ms)),
+        });
+        Ok(self.unbondings.insert(&owner, unbondings)?)
+    }
+
+    pub(crate) async fn claim_unstaked(
+        &mut self,
+        owner: Account,
+        timestamp: Timestamp,
+    ) -> Result<Amount, MemeError> {
+        let (released, unbondings): (Vec<_>, Vec<_>) = self
+            .unbondings_of(owner)
+            .await?
+            .into_iter()
+            .partition(|unbonding| unbonding.release_at <= timestamp);
+
+        let mut amount = Amount::ZERO;
+        for unbonding in released {
+            amount = amount.try_add(unbonding.amount)?;
+        }
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+        let balance = self.balance_of(owner).await.try_add(amount)?;
+
+        if unbondings.is_empty() {
+            self.unbondings.remove(&owner)?;
+        } else {
+            self.unbondings.insert(&owner, unbondings)?;
+        }
+        self.set_balance(owner, balance).await?;
+        Ok(amount)
+    }
+
+    // Allocations are deducted from application balance, then released to beneficiaries
+    pub(crate) async fn initialize_vesting(
+        &mut self,
+        creator: Account,
+        creator_vesting: Option<VestingSchedule>,
+        team_vesting: Vec<TeamAllocation>,
+        timestamp: Timestamp,
+    ) -> Result<(), MemeError> {
+        let allocations = creator_vesting
+            .map(|schedule| TeamAllocation {
+                beneficiary: creator,
+                schedule,
+            })
+            .into_iter()
+            .chain(team_vesting);
+
+        let holder = self.holder.get().unwrap();
+        let mut holder_balance = self.balance_of(holder).await;
+
+        for allocation in allocations {
+            allocation.schedule.validate();
+            assert!(
+                !self.vesting.contains_key(&allocation.beneficiary).await?,
+                "Invalid vesting beneficiary"
+            );
+            assert!(
+                holder_balance >= allocation.schedule.amount,
+                "Invalid initial supply"
+            );
+
+            holder_balance = holder_balance.try_sub(allocation.schedule.amount)?;
+            self.vesting.insert(
+                &allocation.beneficiary,
+                Vesting {
+                    schedule: allocation.schedule,
+                    started_at: timestamp,
+                    claimed: Amount::ZERO,
+                },
+            )?;
+        }
+
+        self.set_balance(holder, holder_balance).await
+    }
+
+    pub(crate) async fn vesting_of(&self, owner: Account) -> Result<Option<Vesting>, MemeError> {
+        Ok(self.vesting.get(&owner).await?)
+    }
+
+    // Airdrop is taken from application balance at meme creation. Returns the airdropped shares
+    pub(crate) async fn initialize_airdrop(
+        &mut self,
+        airdrop: Option<GenesisMinerAirdrop>,
+        genesis_miners: Vec<Account>,
+        timestamp: Timestamp,
+    ) -> Result<Vec<(Account, Amount)>, MemeError> {
+        let Some(airdrop) = airdrop else {
+            return Ok(Vec::new());
+        };
+        airdrop.validate();
+
+        let holder = self.holder.get().unwrap();
+        let holder_balance = self.balance_of(holder).await;
+        assert!(holder_balance >= airdrop.amount, "Invalid initial supply");
+
+        let mut airdropped = Amount::ZERO;
+        let mut shares = Vec::new();
+        for (miner, share) in airdrop.shares(&genesis_miners) {
+            if share == Amount::ZERO {
+                continue;
+            }
+            airdropped = airdropped.try_add(share)?;
+            shares.push((miner, share));
+
+            if airdrop.split == AirdropSplit::Vested {
+                let schedule = VestingSchedule {
+                    amount: share,
+                    ..airdrop.vesting.clone().unwrap()
+                };
+                schedule.validate();
+                self.airdrop_vesting.insert(
+                    &miner,
+                    Vesting {
+                        schedule,
+                        started_at: timestamp,
+                        claimed: Amount::ZERO,
+                    },
+                )?;
+                continue;
+            }
+
+            let balance = self.balance_of(miner).await.try_add(share)?;
+            self.set_balance(miner, balance).await?;
+        }
+
+        self.set_balance(holder, holder_balance.try_sub(airdropped)?)
+            .await?;
+        self.airdropped.set(airdropped);
+        Ok(shares)
+    }
+
+    pub(crate) fn airdropped(&self) -> Amount {
+        *self.airdropped.get()
+    }
+
+    pub(crate) async fn airdrop_vesting_of(
+        &self,
+        owner: Account,
+    ) -> Result<Option<Vesting>, MemeError> {
+        Ok(self.airdrop_vesting.get(&owner).await?)
+    }
+
+    pub(crate) async fn vesting_claimable(
+        &self,
+        owner: Account,
+        timestamp: Timestamp,
+    ) -> Result<Amount, MemeError> {
+        let mut amount = Amount::ZERO;
+        for vesting in [
+            self.vesting_of(owner).await?,
+            self.airdrop_vesting_of(owner).await?,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            amount = amount.try_add(vesting.claimable(timestamp))?;
+        }
+        Ok(amount)
+    }
+
+    pub(crate) async fn claim_vested(
+        &mut self,
+        owner: Account,
+        timestamp: Timestamp,
+    ) -> Result<Amount, MemeError> {
+        let mut vesting = self.vesting_of(owner).await?;
+        let mut airdrop_vesting = self.airdrop_vesting_of(owner).await?;
+        ensure!(
+            vesting.is_some() || airdrop_vesting.is_some(),
+            MemeError::NoVesting
+        );
+
+        let mut amount = Amount::ZERO;
+        for vesting in vesting.iter_mut().chain(airdrop_vesting.iter_mut()) {
+            let claimable = vesting.claimable(timestamp);
+            vesting.claimed = vesting.claimed.try_add(claimable)?;
+            amount = amount.try_add(claimable)?;
+        }
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+        let balance = self.balance_of(owner).await.try_add(amount)?;
+
+        if let Some(vesting) = vesting {
+            self.vesting.insert(&owner, vesting)?;
+        }
+        if let Some(vesting) = airdrop_vesting {
+            self.airdrop_vesting.insert(&owner, vesting)?;
+        }
+        self.set_balance(owner, balance).await?;
+        Ok(amount)
+    }
+
+    // Allocation caps are reserved from application balance, unsold tokens are returned when
+    // presale is closed
+    pub(crate) async fn initialize_presale(
+        &mut self,
+        config: Option<PresaleConfig>,
+    ) -> Result<(), MemeError> {
+        let Some(config) = config else {
+            return Ok(());
+        };
+        config.validate();
+
+        let mut reserved = Amount::ZERO;
+        for allocation in &config.whitelist {
+            assert!(
+                !self.presale_caps.contains_key(&allocation.account).await?,
+                "Invalid whitelist"
+            );
+            self.presale_caps
+                .insert(&allocation.account, allocation.cap)?;
+            reserved = reserved.try_add(allocation.cap)?;
+        }
+
+        let holder = self.holder.get().unwrap();
+        let holder_balance = self.balance_of(holder).await;
+        assert!(holder_balance >= reserved, "Invalid initial supply");
+        self.set_balance(holder, holder_balance.try_sub(reserved)?)
+            .await?;
+
+        self.presale.set(Some(Presale {
+            price: config.price,
+            ends_at: config.ends_at,
+            release_at: config.release_at,
+            reserved,
+            sold: Amount::ZERO,
+            raised: Amount::ZERO,
+            closed: false,
+        }));
+        Ok(())
+    }
+
+    pub(crate) fn presale(&self) -> Option<Presale> {
+        self.presale.get().clone()
+    }
+
+    pub(crate) async fn presale_cap_of(&self, owner: Account) -> Result<Amount, MemeError> {
+        Ok(self.presale_caps.get(&owner).await?.unwrap_or(Amount::ZERO))
+    }
+
+    pub(crate) async fn presale_purchase_of(&self, owner: Account) -> Result<Amount, MemeError> {
+        Ok(self
+            .presale_purchases
+            .get(&owner)
+            .await?
+            .unwrap_or(Amount::ZERO))
+    }
+
+    pub(crate) async fn presale_buy(
+        &mut self,
+        owner: Account,
+        amount: Amount,
+        timestamp: Timestamp,
+    ) -> Result<Amount, MemeError> {
+        let Some(mut presale) = self.presale() else {
+            return Err(MemeError::PresaleDisabled);
+        };
+        ensure!(
+            !presale.closed && timestamp < presale.ends_at,
+            MemeError::PresaleClosed
+        );
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+
+        let Some(cap) = self.presale_caps.get(&owner).await? else {
+            return Err(MemeError::NotWhitelisted);
+        };
+        let tokens = presale.tokens_for(amount);
+        ensure!(tokens > Amount::ZERO, MemeError::InvalidAmount);
+
+        let purchase = self.presale_purchase_of(owner).await?.try_add(tokens)?;
+        ensure!(purchase <= cap, MemeError::ExceedAllocation);
+
+        self.presale_purchases.insert(&owner, purchase)?;
+        presale.sold = presale.sold.try_add(tokens)?;
+        presale.raised = presale.raised.try_add(amount)?;
+        self.presale.set(Some(presale));
+        Ok(tokens)
+    }
+
+    // Returns raised native amount which should be added to initial liquidity
+    pub(crate) async fn close_presale(
+        &mut self,
+        timestamp: Timestamp,
+    ) -> Result<Amount, MemeError> {
+        let Some(mut presale) = self.presale() else {
+            return Err(MemeError::PresaleDisabled);
+        };
+        ensure!(!presale.closed, MemeError::PresaleClosed);
+        ensure!(timestamp >= presale.ends_at, MemeError::PresaleNotEnded);
+
+        let holder = self.holder.get().unwrap();
+        let unsold = presale.reserved.try_sub(presale.sold)?;
+        let holder_balance = self.balance_of(holder).await.try_add(unsold)?;
+        self.set_balance(holder, holder_balance).await?;
+
+        presale.closed = true;
+        let raised = presale.raised;
+        self.presale.set(Some(presale));
+        Ok(raised)
+    }
+
+    pub(crate) async fn claim_presale(
+        &mut self,
+        owner: Account,
+        timestamp: Timestamp,
+    ) -> Result<Amount, MemeError> {
+        let Some(presale) = self.presale() else {
+            return Err(MemeError::PresaleDisabled);
+        };
+        ensure!(timestamp >= presale.release_at, MemeError::PresaleLocked);
+        // Purchased tokens are only claimable after raised funds are added to the pool
+        ensure!(presale.closed, MemeError::PresaleNotClosed);
+
+        let amount = self.presale_purchase_of(owner).await?;
+        ensure!(amount > Amount::ZERO, MemeError::InvalidAmount);
+        let balance = self.balance_of(owner).await.try_add(amount)?;
+
+        self.presale_purchases.remove(&owner)?;
+        self.set_balance(owner, balance).await?;
+        Ok(amount)
+    }
 }
@@ -6,9 +6,11 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use abi::{
+    election::LeaderElectionMethod,
     meme::{
-        InstantiationArgument as MemeInstantiationArgument, Liquidity, Meme, MemeAbi,
-        MemeOperation, MemeParameters, Metadata,
+        HashAlgorithm, InstantiationArgument as MemeInstantiationArgument, Liquidity, Meme,
+        MemeAbi, MemeOperation, MemeParameters, Metadata, MiningConfig, VestingRelease,
+        VestingSchedule,
     },
     policy::open_chain_fee_budget,
     store_type::StoreType,
@@ -35,6 +37,7 @@ struct TestSuite {
 
     pub initial_supply: Amount,
     pub initial_liquidity: Amount,
+    pub creator_allocation: Amount,
 }
 
 impl TestSuite {
@@ -63,6 +66,7 @@ impl TestSuite {
 
             initial_supply: Amount::from_tokens(21000000),
             initial_liquidity: Amount::from_tokens(11000000),
+            creator_allocation: Amount::from_tokens(100),
         }
     }
 
@@ -148,11 +152,34 @@ impl TestSuite {
                 },
                 virtual_initial_liquidity: true,
                 initial_liquidity: None,
+                mining: MiningConfig {
+                    initial_difficulty: 1,
+                    target_block_time_ms: 5000,
+                    retarget_interval: 10,
+                    mining_pool: None,
+                    pow_algorithm: HashAlgorithm::Keccak256,
+                },
+                emission: None,
+                staking: None,
+                transfer_tax: None,
             },
             blob_gateway_application_id: None,
             ams_application_id: None,
             proxy_application_id: None,
             swap_application_id: Some(self.swap_application_id.unwrap()),
+            reward_split: None,
+            // Released right after creation, so creator could transfer in test
+            creator_vesting: Some(VestingSchedule {
+                amount: self.creator_allocation,
+                cliff_ms: 0,
+                duration_ms: 1,
+                release: VestingRelease::Linear,
+                step_ms: 0,
+            }),
+            team_vesting: vec![],
+            presale: None,
+            genesis_miners: vec![],
+            genesis_miner_airdrop: None,
         };
         let parameters = MemeParameters {
             creator: self.chain_owner_account(&self.meme_chain),
@@ -162,6 +189,8 @@ impl TestSuite {
             }),
             virtual_initial_liquidity: true,
             swap_creator_chain_id: self.swap_chain.id(),
+            pow_algorithm: HashAlgorithm::Keccak256,
+            leader_election: LeaderElectionMethod::MultiLeaderRoundRobin,
         };
 
         self.meme_application_id = Some(
@@ -176,6 +205,18 @@ impl TestSuite {
         )
     }
 
+    async fn claim_vested(&self, chain: &ActiveChain) {
+        chain
+            .add_block(|block| {
+                block.with_operation(
+                    self.meme_application_id.unwrap(),
+                    MemeOperation::ClaimVested,
+                );
+            })
+            .await;
+        self.meme_chain.handle_received_messages().await;
+    }
+
     async fn transfer(&self, chain: &ActiveChain, to: Account, amount: Amount) {
         chain
             .add_block(|block| {
@@ -193,7 +234,11 @@ impl TestSuite {
             .add_block(|block| {
                 block.with_operation(
                     self.meme_application_id.unwrap(),
-                    MemeOperation::Approve { spender, amount },
+                    MemeOperation::Approve {
+                        spender,
+                        amount,
+                        expires_at: None,
+                    },
                 );
             })
             .await;
@@ -251,14 +296,8 @@ async fn meme_work_flow_test() {
         suite.initial_supply
     );
 
-    let QueryOutcome { response, .. } = meme_chain
-        .graphql_query(
-            suite.meme_application_id.unwrap(),
-            "query { initialOwnerBalance }",
-        )
-        .await;
-    let initial_owner_balance =
-        Amount::from_str(response["initialOwnerBalance"].as_str().unwrap()).unwrap();
+    // Creator allocation is locked until it's claimed
+    let creator_balance = suite.creator_allocation;
 
     let query = format!(
         "query {{ balanceOf(owner: \"{}\")}}",
@@ -273,17 +312,19 @@ async fn meme_work_flow_test() {
             .initial_supply
             .try_sub(suite.initial_liquidity)
             .unwrap()
-            .try_sub(initial_owner_balance)
+            .try_sub(creator_balance)
             .unwrap(),
     );
 
+    suite.claim_vested(&meme_chain).await;
+
     let query = format!("query {{ balanceOf(owner: \"{}\")}}", meme_owner_account);
     let QueryOutcome { response, .. } = meme_chain
         .graphql_query(suite.meme_application_id.unwrap(), query)
         .await;
     assert_eq!(
         Amount::from_str(response["balanceOf"].as_str().unwrap()).unwrap(),
-        initial_owner_balance,
+        creator_balance,
     );
 
     let query = format!(
@@ -324,7 +365,7 @@ async fn meme_work_flow_test() {
         .await;
     assert_eq!(
         Amount::from_str(response["balanceOf"].as_str().unwrap()).unwrap(),
-        initial_owner_balance
+        creator_balance
             .try_sub(amount)
             .unwrap()
             .try_sub(amount)
@@ -353,7 +394,7 @@ async fn meme_work_flow_test() {
         .await;
     assert_eq!(
         Amount::from_str(response["balanceOf"].as_str().unwrap()).unwrap(),
-        initial_owner_balance
+        creator_balance
             .try_sub(amount)
             .unwrap()
             .try_sub(amount)
@@ -390,7 +431,6 @@ async fn meme_work_flow_test() {
 }
 
 #[tokio::test(flavor = "multi_thread")]
-#[should_panic]
 async fn transfer_insufficient_funds_test() {
     let _ = env_logger::builder().is_test(true).try_init();
 
@@ -404,10 +444,38 @@ async fn transfer_insufficient_funds_test() {
 
     suite.create_meme_application().await;
 
+    let meme_owner_account = suite.chain_owner_account(&meme_chain);
     let user_owner_account = suite.chain_owner_account(&user_chain);
 
     let amount = Amount::from_tokens(101);
     suite
         .transfer(&meme_chain, user_owner_account, amount)
         .await;
+    // Process failure reply from creation chain
+    meme_chain.handle_received_messages().await;
+
+    let query = format!(
+        "query {{ pendingOperations(owner: \"{}\") {{ status reason }} }}",
+        meme_owner_account,
+    );
+    let QueryOutcome { response, .. } = meme_chain
+        .graphql_query(suite.meme_application_id.unwrap(), query)
+        .await;
+    assert_eq!(
+        response["pendingOperations"][0]["status"].as_str().unwrap(),
+        "FAILED"
+    );
+    assert_eq!(
+        response["pendingOperations"][0]["reason"].as_str().unwrap(),
+        "Insufficient funds"
+    );
+
+    let query = format!("query {{ balanceOf(owner: \"{}\")}}", user_owner_account);
+    let QueryOutcome { response, .. } = meme_chain
+        .graphql_query(suite.meme_application_id.unwrap(), query)
+        .await;
+    assert_eq!(
+        Amount::from_str(response["balanceOf"].as_str().unwrap()).unwrap(),
+        Amount::ZERO,
+    );
 }
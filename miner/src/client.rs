@@ -0,0 +1,222 @@
+use abi::{meme::HashAlgorithm, pow::MiningChallenge};
+use anyhow::{anyhow, bail};
+use linera_sdk::linera_base_types::{Account, AccountOwner, BlockHeight, ChainId, CryptoHash};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+/// Everything a miner needs to search nonce for the next block of the meme chain.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Work {
+    pub challenge: MiningChallenge,
+    pub difficulty: u64,
+    pub algorithm: HashAlgorithm,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PowAlgorithmConfig {
+    pow_algorithm: String,
+}
+
+#[derive(Deserialize)]
+struct MemeMining {
+    mining: PowAlgorithmConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MiningInfo {
+    difficulty: u64,
+    previous_hash: CryptoHash,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkResponse {
+    creator_chain_id: ChainId,
+    next_block_height: BlockHeight,
+    mining_info: MiningInfo,
+    meme: MemeMining,
+}
+
+const WORK_QUERY: &str = "query { creatorChainId nextBlockHeight miningInfo { difficulty previousHash } meme { mining { powAlgorithm } } }";
+
+// Enum values are exposed by graphql in screaming snake case
+fn parse_algorithm(value: &str) -> anyhow::Result<HashAlgorithm> {
+    match value {
+        "SHA256D" => Ok(HashAlgorithm::Sha256d),
+        "KECCAK256" => Ok(HashAlgorithm::Keccak256),
+        "BLAKE3" => Ok(HashAlgorithm::Blake3),
+        "SCRYPT" => Ok(HashAlgorithm::Scrypt),
+        _ => bail!("Invalid pow algorithm {}", value),
+    }
+}
+
+pub struct MemeClient {
+    http: reqwest::Client,
+    // Meme application endpoint of node service which tracks meme chain
+    endpoint: String,
+}
+
+impl MemeClient {
+    pub fn new(node_service_url: &str, chain_id: ChainId, application_id: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint: format!(
+                "{}/chains/{}/applications/{}",
+                node_service_url.trim_end_matches('/'),
+                chain_id,
+                application_id
+            ),
+        }
+    }
+
+    async fn request<T: DeserializeOwned>(&self, query: &str) -> anyhow::Result<T> {
+        let response = self
+            .http
+            .post(&self.endpoint)
+            .json(&json!({ "query": query }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        if let Some(errors) = response.get("errors") {
+            bail!("Failed query {}: {}", query, errors);
+        }
+        let data = response
+            .get("data")
+            .cloned()
+            .ok_or(anyhow!("Invalid response {}", response))?;
+        Ok(serde_json::from_value(data)?)
+    }
+
+    pub async fn fetch_work(&self, owner: AccountOwner) -> anyhow::Result<Work> {
+        let response = self.request::<WorkResponse>(WORK_QUERY).await?;
+
+        // Mine operation is always executed on meme chain
+        let chain_id = response.creator_chain_id;
+        Ok(Work {
+            challenge: MiningChallenge {
+                chain_id,
+                height: response.next_block_height,
+                previous_hash: response.mining_info.previous_hash,
+                miner: Account { chain_id, owner },
+            },
+            difficulty: response.mining_info.difficulty,
+            algorithm: parse_algorithm(&response.meme.mining.pow_algorithm)?,
+        })
+    }
+
+    // Node service executes operation returned by application mutation in a new block of the
+    // chain, and returns hash of the block
+    pub async fn submit_mine(&self, nonce: CryptoHash) -> anyhow::Result<Value> {
+        self.request::<Value>(&format!("mutation {{ mine(nonce: \"{}\") }}", nonce))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abi::meme::HashAlgorithm;
+    use linera_sdk::linera_base_types::{AccountOwner, BlockHeight, ChainId, CryptoHash};
+    use mockito::Matcher;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    use super::MemeClient;
+
+    const CHAIN_ID: &str = "aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8";
+    const APPLICATION_ID: &str = "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bad";
+    const PREVIOUS_HASH: &str = "5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc7f";
+
+    fn owner() -> AccountOwner {
+        AccountOwner::from_str("0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e00")
+            .unwrap()
+    }
+
+    fn path() -> String {
+        format!("/chains/{}/applications/{}", CHAIN_ID, APPLICATION_ID)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fetch_work() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", path().as_str())
+            .match_body(Matcher::Regex("miningInfo".to_string()))
+            .with_body(
+                json!({
+                    "data": {
+                        "creatorChainId": CHAIN_ID,
+                        "nextBlockHeight": 12,
+                        "miningInfo": {
+                            "difficulty": 256,
+                            "previousHash": PREVIOUS_HASH,
+                        },
+                        "meme": { "mining": { "powAlgorithm": "BLAKE3" } },
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let chain_id = ChainId::from_str(CHAIN_ID).unwrap();
+        let client = MemeClient::new(&server.url(), chain_id, APPLICATION_ID);
+        let work = client.fetch_work(owner()).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(work.difficulty, 256);
+        assert_eq!(work.algorithm, HashAlgorithm::Blake3);
+        assert_eq!(work.challenge.chain_id, chain_id);
+        assert_eq!(work.challenge.height, BlockHeight::from(12));
+        assert_eq!(
+            work.challenge.previous_hash,
+            CryptoHash::from_str(PREVIOUS_HASH).unwrap()
+        );
+        assert_eq!(work.challenge.miner.chain_id, chain_id);
+        assert_eq!(work.challenge.miner.owner, owner());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn submit_mine() {
+        let nonce = CryptoHash::from_str(PREVIOUS_HASH).unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", path().as_str())
+            .match_body(Matcher::Regex(format!(
+                "mine\\(nonce: \\\\\"{}\\\\\"\\)",
+                nonce
+            )))
+            .with_body(json!({ "data": PREVIOUS_HASH }).to_string())
+            .create_async()
+            .await;
+
+        let chain_id = ChainId::from_str(CHAIN_ID).unwrap();
+        let client = MemeClient::new(&server.url(), chain_id, APPLICATION_ID);
+        let block_hash = client.submit_mine(nonce).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(block_hash, json!(PREVIOUS_HASH));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn submit_mine_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", path().as_str())
+            .with_body(
+                json!({ "errors": [{ "message": "Failed OP: mine: InvalidNonce" }] }).to_string(),
+            )
+            .create_async()
+            .await;
+
+        let chain_id = ChainId::from_str(CHAIN_ID).unwrap();
+        let client = MemeClient::new(&server.url(), chain_id, APPLICATION_ID);
+        let nonce = CryptoHash::from_str(PREVIOUS_HASH).unwrap();
+
+        assert!(client.submit_mine(nonce).await.is_err());
+    }
+}
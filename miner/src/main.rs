@@ -0,0 +1,125 @@
+mod client;
+mod search;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Parser;
+use linera_sdk::linera_base_types::{AccountOwner, ChainId};
+
+use self::client::MemeClient;
+
+/// Mine meme tokens with the same PoW verification as the meme application.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Url of node service which tracks the meme chain
+    #[arg(
+        long,
+        env = "NODE_SERVICE_URL",
+        default_value = "http://localhost:8080"
+    )]
+    node_service_url: String,
+
+    /// Meme chain, where the meme application is created
+    #[arg(long)]
+    chain_id: ChainId,
+
+    /// Meme application id
+    #[arg(long)]
+    application_id: String,
+
+    /// Chain owner who signs mine operation on the meme chain
+    #[arg(long)]
+    owner: AccountOwner,
+
+    /// Workers searching nonce in parallel, default to available cores
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Interval to check if current challenge is already mined by others
+    #[arg(long, default_value_t = 2000)]
+    refresh_interval_ms: u64,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let args = Args::parse();
+    let client = MemeClient::new(&args.node_service_url, args.chain_id, &args.application_id);
+    let threads = args.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1)
+    });
+    let refresh_interval = Duration::from_millis(args.refresh_interval_ms);
+
+    loop {
+        let work = match client.fetch_work(args.owner).await {
+            Ok(work) => work,
+            Err(err) => {
+                log::warn!("Failed fetch work: {}", err);
+                tokio::time::sleep(refresh_interval).await;
+                continue;
+            }
+        };
+        log::info!(
+            "Mining height {} difficulty {} with {:?}",
+            work.challenge.height,
+            work.difficulty,
+            work.algorithm
+        );
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos() as u64;
+        let mut handle = tokio::task::spawn_blocking({
+            let work = work.clone();
+            let stop = stop.clone();
+            move || {
+                search::search(
+                    &work.challenge,
+                    work.algorithm,
+                    work.difficulty,
+                    threads,
+                    seed,
+                    &stop,
+                )
+            }
+        });
+
+        let nonce = loop {
+            tokio::select! {
+                nonce = &mut handle => break nonce?,
+                _ = tokio::time::sleep(refresh_interval) => {
+                    // Restart with new challenge if block is mined or chain moves forward
+                    match client.fetch_work(args.owner).await {
+                        Ok(latest) if latest != work => stop.store(true, Ordering::Relaxed),
+                        Ok(_) => {}
+                        Err(err) => log::warn!("Failed refresh work: {}", err),
+                    }
+                }
+            }
+        };
+
+        let Some(nonce) = nonce else {
+            log::info!("Challenge at height {} is outdated", work.challenge.height);
+            continue;
+        };
+
+        match client.submit_mine(nonce).await {
+            Ok(block) => log::info!(
+                "Mined height {} with nonce {}: {}",
+                work.challenge.height,
+                nonce,
+                block
+            ),
+            Err(err) => log::warn!("Failed submit nonce {}: {}", nonce, err),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use abi::{meme::HashAlgorithm, pow::MiningChallenge};
+use linera_sdk::linera_base_types::CryptoHash;
+
+// Nonce space is split by seed and thread, so workers never try the same nonce
+fn nonce(seed: u64, worker: u64, counter: u64) -> CryptoHash {
+    CryptoHash::from([seed, worker, counter, 0])
+}
+
+/// Search nonce with `threads` workers until one meets difficulty, or until `stop` is set when
+/// the challenge is outdated.
+pub fn search(
+    challenge: &MiningChallenge,
+    algorithm: HashAlgorithm,
+    difficulty: u64,
+    threads: usize,
+    seed: u64,
+    stop: &AtomicBool,
+) -> Option<CryptoHash> {
+    let found = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for worker in 0..threads.max(1) as u64 {
+            let found = &found;
+            scope.spawn(move || {
+                let mut counter = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    let nonce = nonce(seed, worker, counter);
+                    if challenge.verify(&algorithm, nonce, difficulty) {
+                        found.lock().unwrap().get_or_insert(nonce);
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    counter = counter.wrapping_add(1);
+                }
+            });
+        }
+    });
+
+    found.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use abi::{meme::HashAlgorithm, pow::MiningChallenge};
+    use linera_sdk::linera_base_types::{Account, AccountOwner, BlockHeight, ChainId};
+    use std::{str::FromStr, sync::atomic::AtomicBool};
+
+    use super::search;
+
+    fn challenge() -> MiningChallenge {
+        let chain_id =
+            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+                .unwrap();
+        let owner = AccountOwner::from_str(
+            "0x5279b3ae14d3b38e14b65a74aefe44824ea88b25c7841836e9ec77d991a5bc7f",
+        )
+        .unwrap();
+
+        MiningChallenge {
+            chain_id,
+            height: BlockHeight::from(1),
+            previous_hash: abi::pow::genesis_hash(chain_id),
+            miner: Account { chain_id, owner },
+        }
+    }
+
+    #[test]
+    fn search_nonce() {
+        let challenge = challenge();
+        let stop = AtomicBool::new(false);
+
+        let nonce = search(&challenge, HashAlgorithm::Blake3, 256, 4, 0, &stop)
+            .expect("Failed search nonce");
+
+        // Nonce is accepted by the same verification as the contract
+        assert!(challenge.verify(&HashAlgorithm::Blake3, nonce, 256));
+    }
+
+    #[test]
+    fn search_stopped() {
+        let stop = AtomicBool::new(true);
+        assert_eq!(
+            search(
+                &challenge(),
+                HashAlgorithm::Keccak256,
+                u64::MAX,
+                2,
+                0,
+                &stop
+            ),
+            None
+        );
+    }
+}
@@ -6,7 +6,7 @@
 mod state;
 
 use abi::{
-    meme::{MemeAbi, MemeOperation, MemeResponse},
+    meme::{MemeAbi, MemeOperation, MemeResponse, TransferTax},
     policy::open_chain_fee_budget,
     swap::{
         pool::{
@@ -149,6 +149,9 @@ impl Contract for PoolContract {
                     block_timestamp,
                 )
                 .expect("Failed OP: remove liquidity"),
+            PoolOperation::TopUpReserve { amount_0 } => self
+                .on_op_top_up_reserve(amount_0)
+                .expect("Failed OP: top up reserve"),
         }
     }
 
@@ -161,8 +164,11 @@ impl Contract for PoolContract {
             } => self
                 .on_msg_request_fund(token, transfer_id, amount)
                 .expect("Failed MSG: request fund"),
-            PoolMessage::FundSuccess { transfer_id } => self
-                .on_msg_fund_success(transfer_id)
+            PoolMessage::FundSuccess {
+                transfer_id,
+                amount,
+            } => self
+                .on_msg_fund_success(transfer_id, amount)
                 .await
                 .expect("Failed MSG: funds success"),
             PoolMessage::FundFail { transfer_id, error } => self
@@ -235,6 +241,9 @@ impl Contract for PoolContract {
             PoolMessage::NewTransaction { transaction } => self
                 .on_msg_new_transaction(transaction)
                 .expect("Failed MSG: new transaction"),
+            PoolMessage::TopUpReserve { caller, amount_0 } => self
+                .on_msg_top_up_reserve(caller, amount_0)
+                .expect("Failed MSG: top up reserve"),
         }
     }
 
@@ -273,6 +282,10 @@ impl PoolContract {
         self.runtime.application_parameters().token_1
     }
 
+    fn token_0_buy_bps(&mut self) -> u16 {
+        self.runtime.application_parameters().token_0_buy_bps
+    }
+
     fn token_0_creator_chain_id(&mut self) -> ChainId {
         self.runtime
             .application_parameters()
@@ -365,6 +378,16 @@ impl PoolContract {
         Ok(PoolResponse::Ok)
     }
 
+    // Called by meme application after token 0 is transferred to pool application
+    fn on_op_top_up_reserve(&mut self, amount_0: Amount) -> Result<PoolResponse, PoolError> {
+        let caller = self.runtime.authenticated_caller_id().unwrap();
+        self.runtime
+            .prepare_message(PoolMessage::TopUpReserve { caller, amount_0 })
+            .with_authentication()
+            .send_to(self.runtime.application_creator_chain_id());
+        Ok(PoolResponse::Ok)
+    }
+
     fn on_op_swap(
         &mut self,
         amount_0_in: Option<Amount>,
@@ -631,9 +654,15 @@ impl PoolContract {
         Ok(())
     }
 
-    async fn on_msg_fund_success(&mut self, transfer_id: u64) -> Result<(), PoolError> {
+    async fn on_msg_fund_success(
+        &mut self,
+        transfer_id: u64,
+        amount: Amount,
+    ) -> Result<(), PoolError> {
         let mut fund_request = self.state.fund_request(transfer_id).await?;
 
+        // Swap and liquidity are calculated with received amount
+        fund_request.amount_in = amount;
         fund_request.status = FundStatus::Success;
         self.state
             .update_fund_request(transfer_id, fund_request.clone())
@@ -678,7 +707,19 @@ impl PoolContract {
         {
             MemeResponse::Ok => {
                 self.runtime
-                    .prepare_message(PoolMessage::FundSuccess { transfer_id })
+                    .prepare_message(PoolMessage::FundSuccess {
+                        transfer_id,
+                        amount,
+                    })
+                    .with_authentication()
+                    .send_to(message_chain_id);
+            }
+            MemeResponse::Amount(received) => {
+                self.runtime
+                    .prepare_message(PoolMessage::FundSuccess {
+                        transfer_id,
+                        amount: received,
+                    })
                     .with_authentication()
                     .send_to(message_chain_id);
             }
@@ -727,15 +768,19 @@ impl PoolContract {
         to: Option<Account>,
         _block_timestamp: Option<Timestamp>,
     ) -> Result<(), PoolError> {
-        // Here we already funded
+        // Here we already funded. Amount in is what pool received after meme transfer tax, and
+        // amount out is what pool sends
         // 1: Calculate pair token amount
         let amount_0_out = if let Some(amount_1_in) = amount_1_in {
             self.state.calculate_swap_amount_0(amount_1_in)?
         } else {
             Amount::ZERO
         };
+        // Bought meme is taxed when it leaves the pool, slippage is checked with what receiver gets
+        let buy_tax = TransferTax::tax(amount_0_out, self.token_0_buy_bps());
+        let amount_0_received = amount_0_out.try_sub(buy_tax)?;
         if let Some(amount_0_out_min) = amount_0_out_min {
-            if amount_0_out < amount_0_out_min {
+            if amount_0_received < amount_0_out_min {
                 self.refund_amount_in(origin, amount_0_in, amount_1_in);
                 return Err(PoolError::InvalidAmount);
             }
@@ -999,6 +1044,21 @@ impl PoolContract {
         Ok(())
     }
 
+    fn on_msg_top_up_reserve(
+        &mut self,
+        caller: ApplicationId,
+        amount_0: Amount,
+    ) -> Result<(), PoolError> {
+        assert!(
+            caller == self.state.router_application_id() || caller == self.token_0(),
+            "Invalid caller"
+        );
+        assert!(amount_0 > Amount::ZERO, "Invalid amount");
+
+        let timestamp = self.runtime.system_time();
+        self.state.top_up_reserve(amount_0, timestamp)
+    }
+
     fn on_msg_new_transaction(&mut self, transaction: Transaction) -> Result<(), PoolError> {
         // Here we got transaction id
         let transaction = self.state.create_transaction(transaction);
@@ -1027,7 +1087,7 @@ impl PoolContract {
 #[cfg(test)]
 mod tests {
     use abi::{
-        meme::MemeResponse,
+        meme::{MemeResponse, TransferTax},
         swap::pool::{
             InstantiationArgument, PoolAbi, PoolMessage, PoolOperation, PoolParameters,
             PoolResponse,
@@ -1131,13 +1191,62 @@ mod tests {
         };
 
         let transfer_id = pool.state.create_fund_request(fund_request).unwrap();
-        pool.execute_message(PoolMessage::FundSuccess { transfer_id })
-            .await;
+        pool.execute_message(PoolMessage::FundSuccess {
+            transfer_id,
+            amount: Amount::ONE,
+        })
+        .await;
 
         let fund_request = pool.state.fund_request(transfer_id).await.unwrap();
         assert_eq!(fund_request.status, FundStatus::Success);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_fund_success_taxed() {
+        let mut pool = create_and_instantiate_pool(true).await;
+        let owner = Account {
+            chain_id: pool.runtime.chain_id(),
+            owner: pool.runtime.authenticated_signer().unwrap(),
+        };
+
+        let fund_request = FundRequest {
+            from: owner,
+            token: Some(pool.token_0()),
+            amount_in: Amount::ONE,
+            pair_token_amount_out_min: None,
+            to: None,
+            block_timestamp: None,
+            fund_type: FundType::Swap,
+            status: FundStatus::InFlight,
+            error: None,
+            prev_request: None,
+            next_request: None,
+        };
+
+        let transfer_id = pool.state.create_fund_request(fund_request).unwrap();
+        let received = Amount::from_str("0.95").unwrap();
+        pool.execute_message(PoolMessage::FundSuccess {
+            transfer_id,
+            amount: received,
+        })
+        .await;
+
+        let fund_request = pool.state.fund_request(transfer_id).await.unwrap();
+        assert_eq!(fund_request.status, FundStatus::Success);
+        assert_eq!(fund_request.amount_in, received);
+
+        let swap = pool
+            .runtime
+            .created_send_message_requests()
+            .iter()
+            .rev()
+            .find_map(|request| match &request.message {
+                PoolMessage::Swap { amount_0_in, .. } => Some(*amount_0_in),
+                _ => None,
+            });
+        assert_eq!(swap, Some(Some(received)));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn message_fund_fail() {
         let mut pool = create_and_instantiate_pool(true).await;
@@ -1205,6 +1314,57 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_swap_buy_tax_slippage() {
+        let mut pool = create_and_instantiate_pool_with_buy_bps(true, 500).await;
+        let owner = Account {
+            chain_id: pool.runtime.chain_id(),
+            owner: pool.runtime.authenticated_signer().unwrap(),
+        };
+
+        let reserve_0 = pool.state.reserve_0();
+        let reserve_1 = pool.state.reserve_1();
+        let swap_amount_0 = pool.state.calculate_swap_amount_0(Amount::ONE).unwrap();
+        let received = swap_amount_0
+            .try_sub(TransferTax::tax(swap_amount_0, 500))
+            .unwrap();
+
+        // Amount out meets minimum but what receiver gets after buy tax doesn't
+        pool.execute_message(PoolMessage::Swap {
+            origin: owner,
+            amount_0_in: None,
+            amount_1_in: Some(Amount::ONE),
+            amount_0_out_min: Some(swap_amount_0),
+            amount_1_out_min: None,
+            to: None,
+            block_timestamp: None,
+        })
+        .await;
+
+        assert_eq!(reserve_0, pool.state.reserve_0());
+        assert_eq!(reserve_1, pool.state.reserve_1());
+
+        pool.execute_message(PoolMessage::Swap {
+            origin: owner,
+            amount_0_in: None,
+            amount_1_in: Some(Amount::ONE),
+            amount_0_out_min: Some(received),
+            amount_1_out_min: None,
+            to: None,
+            block_timestamp: None,
+        })
+        .await;
+
+        assert_eq!(
+            reserve_0.try_sub(swap_amount_0).unwrap(),
+            pool.state.reserve_0()
+        );
+        assert_eq!(
+            reserve_1.try_add(Amount::ONE).unwrap(),
+            pool.state.reserve_1()
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn message_add_liquidity() {
         let mut pool = create_and_instantiate_pool(true).await;
@@ -1245,6 +1405,58 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_top_up_reserve() {
+        let mut pool = create_and_instantiate_pool(true).await;
+        let caller = pool.state.router_application_id();
+
+        let reserve_0 = pool.state.reserve_0();
+        let reserve_1 = pool.state.reserve_1();
+
+        pool.execute_message(PoolMessage::TopUpReserve {
+            caller,
+            amount_0: Amount::ONE,
+        })
+        .await;
+
+        assert_eq!(
+            reserve_0.try_add(Amount::ONE).unwrap(),
+            pool.state.reserve_0()
+        );
+        assert_eq!(reserve_1, pool.state.reserve_1());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn message_top_up_reserve_from_meme() {
+        let mut pool = create_and_instantiate_pool(true).await;
+        let caller = pool.token_0();
+        let reserve_0 = pool.state.reserve_0();
+
+        pool.execute_message(PoolMessage::TopUpReserve {
+            caller,
+            amount_0: Amount::ONE,
+        })
+        .await;
+
+        assert_eq!(
+            reserve_0.try_add(Amount::ONE).unwrap(),
+            pool.state.reserve_0()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Invalid caller")]
+    async fn message_top_up_reserve_invalid_caller() {
+        let mut pool = create_and_instantiate_pool(true).await;
+        let caller = pool.token_1().unwrap();
+
+        pool.execute_message(PoolMessage::TopUpReserve {
+            caller,
+            amount_0: Amount::ONE,
+        })
+        .await;
+    }
+
     #[test]
     fn cross_application_call() {}
 
@@ -1257,6 +1469,13 @@ mod tests {
     }
 
     async fn create_and_instantiate_pool(virtual_initial_liquidity: bool) -> PoolContract {
+        create_and_instantiate_pool_with_buy_bps(virtual_initial_liquidity, 0).await
+    }
+
+    async fn create_and_instantiate_pool_with_buy_bps(
+        virtual_initial_liquidity: bool,
+        token_0_buy_bps: u16,
+    ) -> PoolContract {
         let _ = env_logger::builder().is_test(true).try_init();
 
         let token_0 = ApplicationId::from_str(
@@ -1293,6 +1512,7 @@ mod tests {
                 virtual_initial_liquidity,
                 token_0_creator_chain_id: chain_id,
                 token_1_creator_chain_id: Some(chain_id),
+                token_0_buy_bps,
             })
             .with_chain_id(chain_id)
             .with_application_id(application_id)
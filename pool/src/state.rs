@@ -192,6 +192,22 @@ impl PoolState {
         Ok(liquidity)
     }
 
+    // Shares are not minted, so the topped up amount is distributed to all liquidity providers
+    pub(crate) fn top_up_reserve(
+        &mut self,
+        amount_0: Amount,
+        block_timestamp: Timestamp,
+    ) -> Result<(), PoolError> {
+        let mut pool: Pool = self.pool();
+        pool.liquid(
+            pool.reserve_0.try_add(amount_0)?,
+            pool.reserve_1,
+            block_timestamp,
+        );
+        self.pool.set(Some(pool));
+        Ok(())
+    }
+
     pub(crate) async fn liquidity(&self, account: Account) -> Result<Amount, PoolError> {
         Ok(self.shares.get(&account).await?.unwrap_or(Amount::ZERO))
     }
@@ -6,9 +6,10 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use abi::{
+    election::LeaderElectionMethod,
     meme::{
-        InstantiationArgument as MemeInstantiationArgument, Liquidity, Meme, MemeAbi,
-        MemeParameters, Metadata,
+        HashAlgorithm, InstantiationArgument as MemeInstantiationArgument, Liquidity, Meme,
+        MemeAbi, MemeParameters, Metadata, MiningConfig,
     },
     policy::open_chain_fee_budget,
     store_type::StoreType,
@@ -181,11 +182,27 @@ impl TestSuite {
                 },
                 virtual_initial_liquidity,
                 initial_liquidity: None,
+                mining: MiningConfig {
+                    initial_difficulty: 1,
+                    target_block_time_ms: 5000,
+                    retarget_interval: 10,
+                    mining_pool: None,
+                    pow_algorithm: HashAlgorithm::Keccak256,
+                },
+                emission: None,
+                staking: None,
+                transfer_tax: None,
             },
             blob_gateway_application_id: None,
             ams_application_id: None,
             proxy_application_id: None,
             swap_application_id: Some(self.swap_application_id.unwrap().forget_abi()),
+            reward_split: None,
+            creator_vesting: None,
+            team_vesting: vec![],
+            presale: None,
+            genesis_miners: vec![],
+            genesis_miner_airdrop: None,
         };
         let parameters = MemeParameters {
             creator: self.chain_owner_account(&self.meme_chain_0),
@@ -195,6 +212,8 @@ impl TestSuite {
             }),
             virtual_initial_liquidity,
             swap_creator_chain_id: self.swap_chain.id(),
+            pow_algorithm: HashAlgorithm::Keccak256,
+            leader_election: LeaderElectionMethod::MultiLeaderRoundRobin,
         };
 
         let meme_bytecode_id = self.meme_chain_0.publish_bytecode_files_in("../meme").await;
@@ -217,6 +236,8 @@ impl TestSuite {
             }),
             virtual_initial_liquidity,
             swap_creator_chain_id: self.swap_chain.id(),
+            pow_algorithm: HashAlgorithm::Keccak256,
+            leader_election: LeaderElectionMethod::MultiLeaderRoundRobin,
         };
         self.meme_application_id_1 = Some(
             self.meme_chain_1
@@ -6,14 +6,17 @@
 mod state;
 
 use abi::{
-    meme::{InstantiationArgument as MemeInstantiationArgument, MemeParameters},
+    election::LeaderElectionMethod,
+    meme::{
+        InstantiationArgument as MemeInstantiationArgument, MemeAbi, MemeOperation, MemeParameters,
+    },
     policy::open_chain_fee_budget,
     proxy::{InstantiationArgument, ProxyAbi, ProxyMessage, ProxyOperation, ProxyResponse},
 };
 use linera_sdk::{
     linera_base_types::{
         Account, AccountOwner, Amount, ApplicationId, ApplicationPermissions, ChainId,
-        ChainOwnership, MessageId, ModuleId, TimeoutConfig, WithContractAbi,
+        ChainOwnership, MessageId, ModuleId, TimeDelta, TimeoutConfig, WithContractAbi,
     },
     views::{RootView, View},
     Contract, ContractRuntime,
@@ -84,6 +87,12 @@ impl Contract for ProxyContract {
             ProxyOperation::DeregisterMiner => self
                 .on_op_deregister_miner()
                 .expect("Failed OP: deregister miner"),
+            ProxyOperation::SetMinerBeneficiary { beneficiary } => self
+                .on_op_set_miner_beneficiary(beneficiary)
+                .expect("Failed OP: set miner beneficiary"),
+            ProxyOperation::SyncMinerBeneficiary { chain_id } => self
+                .on_op_sync_miner_beneficiary(chain_id)
+                .expect("Failed OP: sync miner beneficiary"),
 
             ProxyOperation::CreateMeme {
                 meme_instantiation_argument,
@@ -140,6 +149,14 @@ impl Contract for ProxyContract {
             ProxyMessage::DeregisterMiner { owner } => self
                 .on_msg_deregister_miner(owner)
                 .expect("Failed MSG: deregister miner"),
+            ProxyMessage::SetMinerBeneficiary { owner, beneficiary } => self
+                .on_msg_set_miner_beneficiary(owner, beneficiary)
+                .await
+                .expect("Failed MSG: set miner beneficiary"),
+            ProxyMessage::SyncMinerBeneficiary { owner, chain_id } => self
+                .on_msg_sync_miner_beneficiary(owner, chain_id)
+                .await
+                .expect("Failed MSG: sync miner beneficiary"),
 
             ProxyMessage::CreateMeme {
                 instantiation_argument,
@@ -276,6 +293,30 @@ impl ProxyContract {
         Ok(ProxyResponse::Ok)
     }
 
+    fn on_op_set_miner_beneficiary(
+        &mut self,
+        beneficiary: Account,
+    ) -> Result<ProxyResponse, ProxyError> {
+        let owner = self.owner_account();
+        self.runtime
+            .prepare_message(ProxyMessage::SetMinerBeneficiary { owner, beneficiary })
+            .with_authentication()
+            .send_to(self.runtime.application_creator_chain_id());
+        Ok(ProxyResponse::Ok)
+    }
+
+    fn on_op_sync_miner_beneficiary(
+        &mut self,
+        chain_id: ChainId,
+    ) -> Result<ProxyResponse, ProxyError> {
+        let owner = self.owner_account();
+        self.runtime
+            .prepare_message(ProxyMessage::SyncMinerBeneficiary { owner, chain_id })
+            .with_authentication()
+            .send_to(self.runtime.application_creator_chain_id());
+        Ok(ProxyResponse::Ok)
+    }
+
     fn fund_proxy_chain(&mut self, to: AccountOwner, amount: Amount) {
         assert!(amount > Amount::ZERO, "Invalid fund amount");
 
@@ -351,12 +392,19 @@ impl ProxyContract {
         mut meme_instantiation_argument: MemeInstantiationArgument,
         mut meme_parameters: MemeParameters,
     ) -> Result<ProxyResponse, ProxyError> {
+        // Block time is used as base timeout of meme chain rounds
+        assert!(
+            meme_instantiation_argument.meme.mining.target_block_time_ms > 0,
+            "Invalid target block time"
+        );
+
         meme_instantiation_argument.proxy_application_id =
             Some(self.runtime.application_id().forget_abi());
         meme_instantiation_argument.meme.virtual_initial_liquidity =
             meme_parameters.virtual_initial_liquidity;
         meme_instantiation_argument.meme.initial_liquidity =
             meme_parameters.initial_liquidity.clone();
+        meme_instantiation_argument.meme.mining.pow_algorithm = meme_parameters.pow_algorithm;
 
         // Fund proxy application on the creation chain, it'll fund meme chain for fee and
         // initial liquidity
@@ -482,10 +530,41 @@ impl ProxyContract {
         self.state.register_miner(owner).await
     }
 
+    // Deregistered miner could sync to clear its beneficiary from meme
     fn on_msg_deregister_miner(&mut self, owner: Account) -> Result<(), ProxyError> {
         self.state.deregister_miner(owner)
     }
 
+    async fn on_msg_set_miner_beneficiary(
+        &mut self,
+        owner: Account,
+        beneficiary: Account,
+    ) -> Result<(), ProxyError> {
+        self.state.set_miner_beneficiary(owner, beneficiary).await
+    }
+
+    // Only one meme is updated for each sync, so the cost doesn't grow with memes created
+    async fn on_msg_sync_miner_beneficiary(
+        &mut self,
+        owner: Account,
+        chain_id: ChainId,
+    ) -> Result<(), ProxyError> {
+        let token = self.state.chain_token(chain_id).await?;
+        let beneficiary = self.state.miner_beneficiary(owner).await?;
+        // Miner mines with the same owner on meme chain
+        let miner = Account {
+            chain_id,
+            owner: owner.owner,
+        };
+
+        // Meme application will forward it to its creation chain
+        let call = MemeOperation::SetMinerBeneficiary { miner, beneficiary };
+        let _ = self
+            .runtime
+            .call_application(true, token.with_abi::<MemeAbi>(), &call);
+        Ok(())
+    }
+
     async fn meme_chain_owner_weights(&self) -> Result<Vec<(AccountOwner, u64)>, ProxyError> {
         let mut owner_weights = Vec::new();
 
@@ -499,11 +578,24 @@ impl ProxyContract {
         Ok(owner_weights)
     }
 
-    async fn create_meme_chain(&mut self) -> Result<(MessageId, ChainId), ProxyError> {
+    async fn create_meme_chain(
+        &mut self,
+        leader_election: LeaderElectionMethod,
+        target_block_time_ms: u64,
+    ) -> Result<(MessageId, ChainId), ProxyError> {
+        let timeout_config = match leader_election {
+            // Proposer is decided by mined nonce, not by rounds
+            LeaderElectionMethod::ProofOfWork => TimeoutConfig::default(),
+            // Offline leader is skipped after one block time
+            _ => TimeoutConfig {
+                base_timeout: TimeDelta::from_millis(target_block_time_ms),
+                ..TimeoutConfig::default()
+            },
+        };
         let ownership = ChainOwnership::multiple(
             self.meme_chain_owner_weights().await?,
-            0, // TODO: run in single leader mode firstly, will be updated when multi leader mode done
-            TimeoutConfig::default(),
+            leader_election.multi_leader_rounds(),
+            timeout_config,
         );
         let application_id = self.runtime.application_id().forget_abi();
         // We have to let meme application change permissions
@@ -565,11 +657,19 @@ impl ProxyContract {
 
     async fn on_creation_chain_msg_create_meme(
         &mut self,
-        instantiation_argument: MemeInstantiationArgument,
+        mut instantiation_argument: MemeInstantiationArgument,
         parameters: MemeParameters,
     ) -> Result<(), ProxyError> {
+        // Only approved genesis miners could get airdrop
+        instantiation_argument.genesis_miners = self.state.genesis_miners().await?;
+
         // 1: create a new chain which allow and mandary proxy
-        let (message_id, chain_id) = self.create_meme_chain().await?;
+        let (message_id, chain_id) = self
+            .create_meme_chain(
+                parameters.leader_election,
+                instantiation_argument.meme.mining.target_block_time_ms,
+            )
+            .await?;
 
         // Fund created meme chain with initial liquidity
         self.fund_meme_chain_initial_liquidity(chain_id, parameters.clone());
@@ -716,13 +816,15 @@ impl ProxyContract {
 
 #[cfg(test)]
 mod tests {
-    use abi::proxy::{
-        InstantiationArgument, ProxyAbi, ProxyMessage, ProxyOperation, ProxyResponse,
+    use abi::{
+        meme::MemeResponse,
+        proxy::{InstantiationArgument, ProxyAbi, ProxyMessage, ProxyOperation, ProxyResponse},
     };
     use futures::FutureExt as _;
     use linera_sdk::{
+        bcs,
         linera_base_types::{
-            Account, AccountOwner, ApplicationId, ChainId, ChainOwnership, ModuleId,
+            Account, AccountOwner, ApplicationId, ChainId, ChainOwnership, MessageId, ModuleId,
         },
         util::BlockingWait,
         views::View,
@@ -789,9 +891,112 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn msg_set_miner_beneficiary() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut proxy = create_and_instantiate_proxy();
+
+        let chain_id =
+            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+                .unwrap();
+        let owner = AccountOwner::from_str(
+            "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e01",
+        )
+        .unwrap();
+        let owner = Account { chain_id, owner };
+        let beneficiary = AccountOwner::from_str(
+            "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e02",
+        )
+        .unwrap();
+        let beneficiary = Account {
+            chain_id,
+            owner: beneficiary,
+        };
+
+        proxy
+            .execute_message(ProxyMessage::RegisterMiner { owner })
+            .await;
+        assert_eq!(proxy.state.miner_beneficiary(owner).await.unwrap(), None);
+
+        proxy
+            .execute_message(ProxyMessage::SetMinerBeneficiary { owner, beneficiary })
+            .await;
+        assert_eq!(
+            proxy.state.miner_beneficiary(owner).await.unwrap(),
+            Some(beneficiary)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn msg_sync_miner_beneficiary() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut proxy = create_and_instantiate_proxy();
+
+        let chain_id =
+            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+                .unwrap();
+        let owner = AccountOwner::from_str(
+            "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e01",
+        )
+        .unwrap();
+        let owner = Account { chain_id, owner };
+        let message_id = MessageId::from_str("dad01517c7a3c428ea903253a9e59964e8db06d323a9bd3f4c74d6366832bdbf801200000000000000000000").unwrap();
+        let token = ApplicationId::from_str(
+            "b10ac11c3569d9e1b6e22fe50f8c1de8b33a01173b4563c614aa07d8b8eb5bae",
+        )
+        .unwrap();
+
+        proxy
+            .state
+            .create_chain(chain_id, message_id, 0.into())
+            .await
+            .unwrap();
+        proxy
+            .state
+            .create_chain_token(chain_id, token)
+            .await
+            .unwrap();
+        assert_eq!(proxy.state.chain_token(chain_id).await.unwrap(), token);
+
+        proxy
+            .execute_message(ProxyMessage::RegisterMiner { owner })
+            .await;
+        proxy
+            .execute_message(ProxyMessage::SyncMinerBeneficiary { owner, chain_id })
+            .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "Failed MSG: sync miner beneficiary: NotExists")]
+    async fn msg_sync_miner_beneficiary_without_meme() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut proxy = create_and_instantiate_proxy();
+
+        let chain_id =
+            ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
+                .unwrap();
+        let owner = AccountOwner::from_str(
+            "0x02e900512d2fca22897f80a2f6932ff454f2752ef7afad18729dd25e5b5b6e01",
+        )
+        .unwrap();
+        let owner = Account { chain_id, owner };
+
+        proxy
+            .execute_message(ProxyMessage::SyncMinerBeneficiary { owner, chain_id })
+            .await;
+    }
+
     #[test]
     fn cross_application_call() {}
 
+    fn mock_application_call(
+        _authenticated: bool,
+        _application_id: ApplicationId,
+        _operation: Vec<u8>,
+    ) -> Vec<u8> {
+        bcs::to_bytes(&MemeResponse::Ok).unwrap()
+    }
+
     fn create_and_instantiate_proxy() -> ProxyContract {
         let chain_id =
             ChainId::from_str("aee928d4bf3880353b4a3cd9b6f88e6cc6e5ed050860abae439e7782e9b2dfe8")
@@ -812,7 +1017,8 @@ mod tests {
             .with_chain_id(chain_id)
             .with_application_creator_chain_id(chain_id)
             .with_chain_ownership(ChainOwnership::single(owner))
-            .with_application_id(application_id);
+            .with_application_id(application_id)
+            .with_call_application_handler(mock_application_call);
         let mut contract = ProxyContract {
             state: ProxyState::load(runtime.root_view_storage_context())
                 .blocking_wait()
@@ -74,6 +74,10 @@ impl QueryRoot {
         self.state.miners().await.unwrap()
     }
 
+    async fn miner_beneficiary(&self, owner: Account) -> Option<Account> {
+        self.state.miner_beneficiary(owner).await.unwrap()
+    }
+
     async fn meme_chains(&self) -> Vec<Chain> {
         self.state
             .chains
@@ -265,10 +265,47 @@ impl ProxyState {
                 == 0,
             "Already registered"
         );
-        Ok(self.miners.insert(&owner, Miner { owner })?)
+        Ok(self.miners.insert(
+            &owner,
+            Miner {
+                owner,
+                beneficiary: None,
+            },
+        )?)
     }
 
     pub(crate) fn deregister_miner(&mut self, owner: Account) -> Result<(), ProxyError> {
         Ok(self.miners.remove(&owner)?)
     }
+
+    pub(crate) async fn set_miner_beneficiary(
+        &mut self,
+        owner: Account,
+        beneficiary: Account,
+    ) -> Result<(), ProxyError> {
+        let Some(mut miner) = self.miners.get(&owner).await? else {
+            return Err(ProxyError::NotExists);
+        };
+        miner.beneficiary = Some(beneficiary);
+        Ok(self.miners.insert(&owner, miner)?)
+    }
+
+    pub(crate) async fn miner_beneficiary(
+        &self,
+        owner: Account,
+    ) -> Result<Option<Account>, ProxyError> {
+        Ok(self
+            .miners
+            .get(&owner)
+            .await?
+            .and_then(|miner| miner.beneficiary))
+    }
+
+    pub(crate) async fn chain_token(&self, chain_id: ChainId) -> Result<ApplicationId, ProxyError> {
+        self.chains
+            .get(&chain_id)
+            .await?
+            .and_then(|chain| chain.token)
+            .ok_or(ProxyError::NotExists)
+    }
 }
@@ -159,23 +159,14 @@ async fn proxy_create_meme_real_initial_liquidity_single_owner_test() {
             .unwrap();
     assert_eq!(meme_application.is_some(), true);
 
-    // Check meme creator balance
-    let QueryOutcome { response, .. } = meme_chain
-        .graphql_query(
-            meme_application.unwrap().with_abi::<MemeAbi>(),
-            "query { initialOwnerBalance }",
-        )
-        .await;
-    let initial_owner_balance =
-        Amount::from_str(response["initialOwnerBalance"].as_str().unwrap()).unwrap();
-
+    // Meme creator doesn't hold any unlocked balance
     let query = format!("query {{ balanceOf(owner: \"{}\")}}", meme_user_owner);
     let QueryOutcome { response, .. } = meme_chain
         .graphql_query(meme_application.unwrap().with_abi::<MemeAbi>(), query)
         .await;
     assert_eq!(
         Amount::from_str(response["balanceOf"].as_str().unwrap()).unwrap(),
-        initial_owner_balance,
+        Amount::ZERO,
     );
 
     proxy_chain.handle_received_messages().await;
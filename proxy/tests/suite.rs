@@ -6,9 +6,10 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use abi::{
+    election::LeaderElectionMethod,
     meme::{
-        InstantiationArgument as MemeInstantiationArgument, Liquidity, Meme, MemeParameters,
-        Metadata,
+        HashAlgorithm, InstantiationArgument as MemeInstantiationArgument, Liquidity, Meme,
+        MemeParameters, Metadata, MiningConfig,
     },
     proxy::{InstantiationArgument, ProxyAbi, ProxyOperation},
     store_type::StoreType,
@@ -223,6 +224,16 @@ impl TestSuite {
                                 },
                                 virtual_initial_liquidity,
                                 initial_liquidity: None,
+                                mining: MiningConfig {
+                                    initial_difficulty: 1,
+                                    target_block_time_ms: 5000,
+                                    retarget_interval: 10,
+                                    mining_pool: None,
+                                    pow_algorithm: HashAlgorithm::Keccak256,
+                                },
+                                emission: None,
+                                staking: None,
+                                transfer_tax: None,
                             },
                             blob_gateway_application_id: None,
                             ams_application_id: None,
@@ -230,6 +241,12 @@ impl TestSuite {
                             swap_application_id: Some(
                                 self.swap_application_id.unwrap().forget_abi(),
                             ),
+                            reward_split: None,
+                            creator_vesting: None,
+                            team_vesting: vec![],
+                            presale: None,
+                            genesis_miners: vec![],
+                            genesis_miner_airdrop: None,
                         },
                         meme_parameters: MemeParameters {
                             creator: self.chain_owner_account(chain),
@@ -239,6 +256,8 @@ impl TestSuite {
                             }),
                             virtual_initial_liquidity,
                             swap_creator_chain_id: self.swap_chain.id(),
+                            pow_algorithm: HashAlgorithm::Keccak256,
+                            leader_election: LeaderElectionMethod::MultiLeaderRoundRobin,
                         },
                     },
                 );
@@ -74,6 +74,7 @@ impl Contract for SwapContract {
                 // Only for creator to initialize pool
                 virtual_liquidity,
                 to,
+                buy_bps,
             } => self
                 .on_call_initialize_liquidity(
                     creator,
@@ -83,6 +84,7 @@ impl Contract for SwapContract {
                     amount_1,
                     virtual_liquidity,
                     to,
+                    buy_bps,
                 )
                 .expect("Failed OP: initialize liquidity"),
             SwapOperation::CreatePool {
@@ -123,6 +125,13 @@ impl Contract for SwapContract {
                     reserve_1,
                 )
                 .expect("Failed OP: update pool"),
+            SwapOperation::TopUpLiquidity {
+                token_0_creator_chain_id,
+                token_0,
+                amount_0,
+            } => self
+                .on_call_top_up_liquidity(token_0_creator_chain_id, token_0, amount_0)
+                .expect("Failed OP: top up liquidity"),
         }
     }
 
@@ -142,6 +151,7 @@ impl Contract for SwapContract {
                 // Only for creator to initialize pool
                 virtual_liquidity,
                 to,
+                buy_bps,
             } => self
                 .on_msg_initialize_liquidity(
                     creator,
@@ -151,6 +161,7 @@ impl Contract for SwapContract {
                     amount_1,
                     virtual_liquidity,
                     to,
+                    buy_bps,
                 )
                 .await
                 .expect("Failed MSG: initialize liquidity"),
@@ -166,6 +177,7 @@ impl Contract for SwapContract {
                 virtual_initial_liquidity,
                 to,
                 user_pool,
+                token_0_buy_bps,
             } => self
                 .on_msg_create_pool(
                     creator,
@@ -179,6 +191,7 @@ impl Contract for SwapContract {
                     virtual_initial_liquidity,
                     to,
                     user_pool,
+                    token_0_buy_bps,
                 )
                 .expect("Failed MSG: create pool"),
             SwapMessage::PoolCreated {
@@ -263,6 +276,10 @@ impl Contract for SwapContract {
                 )
                 .await
                 .expect("Failed MSG: update pool"),
+            SwapMessage::TopUpLiquidity { token_0, amount_0 } => self
+                .on_msg_top_up_liquidity(token_0, amount_0)
+                .await
+                .expect("Failed MSG: top up liquidity"),
         }
     }
 
@@ -399,6 +416,7 @@ impl SwapContract {
         amount_1: Amount,
         virtual_liquidity: bool,
         to: Option<Account>,
+        buy_bps: u16,
     ) -> Result<SwapResponse, SwapError> {
         let caller_id = self.runtime.authenticated_caller_id().unwrap();
         let chain_id = self.runtime.chain_id();
@@ -430,6 +448,7 @@ impl SwapContract {
                 amount_1,
                 virtual_liquidity,
                 to,
+                buy_bps,
             })
             .with_authentication()
             .send_to(self.runtime.application_creator_chain_id());
@@ -494,6 +513,27 @@ impl SwapContract {
         Ok(SwapResponse::Ok)
     }
 
+    fn on_call_top_up_liquidity(
+        &mut self,
+        token_0_creator_chain_id: ChainId,
+        token_0: ApplicationId,
+        amount_0: Amount,
+    ) -> Result<SwapResponse, SwapError> {
+        let caller_id = self.runtime.authenticated_caller_id().unwrap();
+        let chain_id = self.runtime.chain_id();
+
+        assert!(token_0 == caller_id, "Invalid caller");
+        assert!(chain_id == token_0_creator_chain_id, "Invalid caller");
+
+        // Here allowance is already approved to swap application on swap creation chain
+        self.runtime
+            .prepare_message(SwapMessage::TopUpLiquidity { token_0, amount_0 })
+            .with_authentication()
+            .send_to(self.runtime.application_creator_chain_id());
+
+        Ok(SwapResponse::Ok)
+    }
+
     // Pool application is run on its own chain
     async fn create_pool(
         &mut self,
@@ -508,6 +548,7 @@ impl SwapContract {
         to: Option<Account>,
         _deadline: Option<Timestamp>,
         user_pool: bool,
+        token_0_buy_bps: u16,
     ) -> Result<(), SwapError> {
         // For initial pool, all assets should be already authenticated when we're here
         // For user pool, we just create a pool, then notify user to add liquidity
@@ -534,6 +575,7 @@ impl SwapContract {
                 virtual_initial_liquidity: virtual_liquidity,
                 to,
                 user_pool,
+                token_0_buy_bps,
             })
             .with_authentication()
             .send_to(chain_id);
@@ -551,6 +593,7 @@ impl SwapContract {
         amount_1: Amount,
         virtual_liquidity: bool,
         to: Option<Account>,
+        buy_bps: u16,
     ) -> Result<(), SwapError> {
         self.create_pool(
             creator,
@@ -564,6 +607,7 @@ impl SwapContract {
             to,
             None,
             false,
+            buy_bps,
         )
         .await
     }
@@ -581,6 +625,7 @@ impl SwapContract {
         virtual_initial_liquidity: bool,
         to: Option<Account>,
         user_pool: bool,
+        token_0_buy_bps: u16,
     ) -> Result<(), SwapError> {
         // Run on pool chain
         let application_id = self.runtime.application_id().forget_abi();
@@ -598,6 +643,7 @@ impl SwapContract {
                     virtual_initial_liquidity,
                     token_0_creator_chain_id,
                     token_1_creator_chain_id,
+                    token_0_buy_bps,
                 },
                 &PoolInstantiationArgument {
                     amount_0: if late_add_liquidity {
@@ -805,6 +851,8 @@ impl SwapContract {
             to,
             None,
             true,
+            // Meme taxes only its native pool with buy rate
+            0,
         )
         .await
     }
@@ -831,6 +879,29 @@ impl SwapContract {
             )
             .await
     }
+
+    async fn on_msg_top_up_liquidity(
+        &mut self,
+        token_0: ApplicationId,
+        amount_0: Amount,
+    ) -> Result<(), SwapError> {
+        // Meme native pool is created when meme application is created. If it's not created yet,
+        // allowance will be kept for swap application
+        let Some(pool) = self.state.get_pool_exchangable(token_0, None).await? else {
+            return Ok(());
+        };
+        let pool_application = pool.pool_application;
+
+        // Meme application tops up pool reserve after the transfer is done on meme creation chain
+        let call = MemeOperation::InitializeLiquidity {
+            to: pool_application,
+            amount: amount_0,
+        };
+        let _ = self
+            .runtime
+            .call_application(true, token_0.with_abi::<MemeAbi>(), &call);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -886,6 +957,7 @@ mod tests {
                 amount_1: Amount::ONE,
                 virtual_liquidity: false,
                 to: None,
+                buy_bps: 0,
             })
             .await;
 